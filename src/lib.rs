@@ -0,0 +1,5757 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct Coordinate {
+    pub x: usize,
+    pub y: usize,
+}
+
+pub type Player = u32;
+
+/// Reserved player of blocker tokens, shown as `#`. They occupy their cell
+/// and can't be swapped, but neighbor nobody and score for nobody, see
+/// `Board::place_neutral`.
+pub const NEUTRAL: Player = Player::MAX;
+pub type Cell = Option<Token>;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Token {
+    pub player: Player,
+    pub locked: bool,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let player = match self.player {
+            0 => 'x',
+            1 => 'o',
+            NEUTRAL => '#',
+            _ => 'h',
+        };
+
+        if self.locked {
+            write!(f, "{}", player.to_uppercase())?;
+        } else {
+            write!(f, "{}", player)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn c(x: usize, y: usize) -> Coordinate {
+    Coordinate { x, y }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum WinState {
+    NotOver,
+    Draw,
+    Winner(Player),
+}
+
+/// How distance between two cells is measured when looking for neighbors
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Distance {
+    /// `|dx| + |dy|`, radius 1 gives the 4 orthogonal neighbors
+    Manhattan,
+    /// `max(|dx|, |dy|)`, radius 1 gives all 8 surrounding cells
+    Chebyshev,
+}
+
+impl Distance {
+    pub fn between(&self, a: Coordinate, b: Coordinate) -> usize {
+        self.of_offset(a.x.abs_diff(b.x), a.y.abs_diff(b.y))
+    }
+
+    fn of_offset(&self, dx: usize, dy: usize) -> usize {
+        match self {
+            Distance::Manhattan => dx + dy,
+            Distance::Chebyshev => dx.max(dy),
+        }
+    }
+
+    /// How many cells are within `radius` of a cell on an unbounded board
+    pub fn max_neighbors(&self, radius: usize) -> usize {
+        match self {
+            Distance::Manhattan => 2 * radius * (radius + 1),
+            Distance::Chebyshev => (2 * radius + 1).pow(2) - 1,
+        }
+    }
+}
+
+/// Rule variations that change how the game is played and scored
+///
+/// Rules missing from a save get their default value.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ruleset {
+    /// Cells within this distance of each other are neighbors
+    radius: usize,
+    distance: Distance,
+    /// Players may swap two unlocked tokens instead of placing one. Without
+    /// swaps the game ends once the board is full.
+    allow_swaps: bool,
+    /// Where tokens may be placed
+    placement: PlacementRule,
+    /// If a swap can score a victory point, the player must play such a swap
+    forced_scoring_swaps: bool,
+    /// How the winner is decided once the game is over
+    scoring: Scoring,
+    /// Edges wrap around, making the board a torus
+    wrap: bool,
+    player_count: u32,
+    /// A token scores when more than this many of its neighbors are its own
+    victory_threshold: usize,
+    /// How players tied on score are separated
+    tie_break: TieBreak,
+    /// Added to player 1's final score to make up for moving second
+    komi: i32,
+    /// The game ends as soon as a player's score reaches this
+    target_points: Option<usize>,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset {
+            radius: 1,
+            distance: Distance::Manhattan,
+            allow_swaps: true,
+            placement: PlacementRule::Anywhere,
+            forced_scoring_swaps: false,
+            scoring: Scoring::VictoryPoints,
+            wrap: false,
+            player_count: 2,
+            victory_threshold: 3,
+            tie_break: TieBreak::DrawOnTie,
+            komi: 0,
+            target_points: None,
+        }
+    }
+}
+
+/// Most players a ruleset can have
+pub const MAX_PLAYERS: Player = 16;
+
+impl Ruleset {
+    /// Why the rules can't be played on a board of `size`, if they can't.
+    /// Shared by `BoardBuilder::build` and `Board::from_json`.
+    fn check(&self, size: (usize, usize)) -> Result<(), &'static str> {
+        if self.radius == 0 {
+            return Err("radius must be at least 1");
+        }
+        if self.radius > size.0.max(size.1) {
+            return Err("radius is larger than the board");
+        }
+        if self.player_count < 2 {
+            return Err("need at least two players");
+        }
+        if self.player_count > MAX_PLAYERS {
+            return Err("too many players");
+        }
+        if self.target_points == Some(0) {
+            return Err("target points must be at least 1");
+        }
+        if self.victory_threshold >= self.distance.max_neighbors(self.radius) {
+            return Err("victory threshold is higher than any cell's neighbor count");
+        }
+
+        Ok(())
+    }
+}
+
+/// Decides each player's final score, higher is better
+pub trait ScoringRule {
+    fn scores(&self, board: &Board) -> Vec<usize>;
+}
+
+/// The standard rule, see `Board::count_victory_points`
+pub struct VictoryPointsRule;
+
+impl ScoringRule for VictoryPointsRule {
+    fn scores(&self, board: &Board) -> Vec<usize> {
+        board.count_victory_points()
+    }
+}
+
+/// Score is the size of the player's largest orthogonally connected group
+pub struct LargestGroupRule;
+
+impl ScoringRule for LargestGroupRule {
+    fn scores(&self, board: &Board) -> Vec<usize> {
+        board.largest_groups()
+    }
+}
+
+/// Scoring rules a `Ruleset` can pick from
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Scoring {
+    VictoryPoints,
+    LargestGroup,
+}
+
+impl Scoring {
+    pub fn rule(&self) -> &'static dyn ScoringRule {
+        match self {
+            Scoring::VictoryPoints => &VictoryPointsRule,
+            Scoring::LargestGroup => &LargestGroupRule,
+        }
+    }
+}
+
+/// Restricts which empty cells a player may place on
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum PlacementRule {
+    Anywhere,
+    /// Next to one of the player's own tokens orthogonally, unless they have
+    /// none on the board yet
+    AdjacentToOwn,
+}
+
+/// Separates players tied on score at the end of the game. If the tie-break
+/// is tied as well, the game is a draw.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum TieBreak {
+    DrawOnTie,
+    /// The tied player with the most locked tokens wins
+    MostLocked,
+    /// The tied player who got there with the fewest tokens placed wins
+    FirstToReach,
+}
+
+/// Named two-player openings for `Board::from_start`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum StartPosition {
+    Empty,
+    /// Player 0 above and below the center cell, player 1 left and right of it
+    Cross,
+    /// Player 0 in the top-left and bottom-right corners, player 1 in the other two
+    Corners,
+}
+
+impl StartPosition {
+    /// Pre-placed tokens on a board of `size`, or why they don't fit
+    fn tokens(&self, size: (usize, usize)) -> Result<Vec<(Coordinate, Player)>, GameError> {
+        let (width, height) = size;
+        match self {
+            StartPosition::Empty => Ok(Vec::new()),
+            StartPosition::Cross => {
+                if width < 3 || height < 3 {
+                    return Err(GameError::InvalidConfig("cross start needs at least 3x3"));
+                }
+                let (x, y) = (width / 2, height / 2);
+                Ok(vec![
+                    (c(x, y - 1), 0),
+                    (c(x - 1, y), 1),
+                    (c(x + 1, y), 1),
+                    (c(x, y + 1), 0),
+                ])
+            }
+            StartPosition::Corners => {
+                if width < 2 || height < 2 {
+                    return Err(GameError::InvalidConfig("corners start needs at least 2x2"));
+                }
+                Ok(vec![
+                    (c(0, 0), 0),
+                    (c(width - 1, 0), 1),
+                    (c(0, height - 1), 1),
+                    (c(width - 1, height - 1), 0),
+                ])
+            }
+        }
+    }
+}
+
+/// Configures a `Board` beyond what the constructors cover, see `Board::builder`
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    size: (usize, usize),
+    rules: Ruleset,
+    playable: Option<Vec<bool>>,
+    turn_schedule: Option<Vec<Player>>,
+}
+
+impl BoardBuilder {
+    pub fn rules(mut self, rules: Ruleset) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn radius(mut self, radius: usize) -> Self {
+        self.rules.radius = radius;
+        self
+    }
+
+    pub fn distance(mut self, distance: Distance) -> Self {
+        self.rules.distance = distance;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.rules.wrap = wrap;
+        self
+    }
+
+    pub fn player_count(mut self, player_count: u32) -> Self {
+        self.rules.player_count = player_count;
+        self
+    }
+
+    pub fn victory_threshold(mut self, victory_threshold: usize) -> Self {
+        self.rules.victory_threshold = victory_threshold;
+        self
+    }
+
+    pub fn allow_swaps(mut self, allow_swaps: bool) -> Self {
+        self.rules.allow_swaps = allow_swaps;
+        self
+    }
+
+    pub fn placement(mut self, placement: PlacementRule) -> Self {
+        self.rules.placement = placement;
+        self
+    }
+
+    pub fn forced_scoring_swaps(mut self, forced_scoring_swaps: bool) -> Self {
+        self.rules.forced_scoring_swaps = forced_scoring_swaps;
+        self
+    }
+
+    pub fn scoring(mut self, scoring: Scoring) -> Self {
+        self.rules.scoring = scoring;
+        self
+    }
+
+    pub fn tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.rules.tie_break = tie_break;
+        self
+    }
+
+    pub fn komi(mut self, komi: i32) -> Self {
+        self.rules.komi = komi;
+        self
+    }
+
+    pub fn target_points(mut self, target_points: usize) -> Self {
+        self.rules.target_points = Some(target_points);
+        self
+    }
+
+    pub fn playable_mask(mut self, playable: Vec<bool>) -> Self {
+        self.playable = Some(playable);
+        self
+    }
+
+    /// Players move in this order, starting over at the end, e.g.
+    /// `[0, 1, 2, 3]` for 2v2 teams or `[0, 0, 1]` for a handicap
+    pub fn turn_schedule(mut self, schedule: Vec<Player>) -> Self {
+        self.turn_schedule = Some(schedule);
+        self
+    }
+
+    /// Checks that the options make sense together and builds the board
+    pub fn build(self) -> Result<Board, GameError> {
+        let rules = &self.rules;
+        if self.size.0 == 0 || self.size.1 == 0 {
+            return Err(GameError::InvalidConfig("board has no cells"));
+        }
+        rules.check(self.size).map_err(GameError::InvalidConfig)?;
+        if let Some(playable) = &self.playable {
+            if playable.len() != self.size.0 * self.size.1 {
+                return Err(GameError::InvalidConfig(
+                    "mask must have one entry per cell",
+                ));
+            }
+        }
+
+        if let Some(schedule) = &self.turn_schedule {
+            if schedule.is_empty() {
+                return Err(GameError::InvalidConfig("turn schedule is empty"));
+            }
+            if schedule.iter().any(|player| *player >= rules.player_count) {
+                return Err(GameError::InvalidConfig(
+                    "turn schedule names a player that isn't in the game",
+                ));
+            }
+        }
+
+        Ok(Board {
+            current_turn: self
+                .turn_schedule
+                .as_ref()
+                .map_or(0, |schedule| schedule[0]),
+            playable: self.playable,
+            turn_schedule: self.turn_schedule,
+            ..Board::with_rules(self.size, self.rules)
+        })
+    }
+}
+
+/// Current state of the game board,
+///
+/// plus a method to advance the state by playing a move
+///
+/// Equality and hashing only look at the position, not at `last_move`.
+#[derive(Serialize, Deserialize)]
+pub struct Board {
+    current_turn: Player,
+    cells: Vec<Cell>,
+    size: (usize, usize),
+    rules: Ruleset,
+    /// Which cells are part of the board, `None` if all of them are
+    playable: Option<Vec<bool>>,
+    /// Move that produced this board, set by `advance`
+    #[serde(default)]
+    last_move: Option<Move>,
+    /// Order players move in, see `BoardBuilder::turn_schedule`. `None`
+    /// takes turns 0, 1, 2 and so on.
+    #[serde(default)]
+    turn_schedule: Option<Vec<Player>>,
+    /// Position of the player to move in `turn_schedule`
+    #[serde(default)]
+    turn_index: usize,
+}
+
+/// `clone_from` (and so `clone_into`) reuses the destination's cell
+/// allocation, which keeps the allocator out of the search loop
+impl Clone for Board {
+    fn clone(&self) -> Self {
+        Board {
+            current_turn: self.current_turn,
+            cells: self.cells.clone(),
+            size: self.size,
+            rules: self.rules,
+            playable: self.playable.clone(),
+            last_move: self.last_move,
+            turn_schedule: self.turn_schedule.clone(),
+            turn_index: self.turn_index,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.current_turn = source.current_turn;
+        self.cells.clone_from(&source.cells);
+        self.size = source.size;
+        self.rules = source.rules;
+        self.playable.clone_from(&source.playable);
+        self.last_move = source.last_move;
+        self.turn_schedule.clone_from(&source.turn_schedule);
+        self.turn_index = source.turn_index;
+    }
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.current_turn == other.current_turn
+            && self.size == other.size
+            && self.rules == other.rules
+            && self.cells == other.cells
+            && self.playable == other.playable
+            && self.turn_schedule == other.turn_schedule
+            && self.turn_index == other.turn_index
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.current_turn.hash(state);
+        self.size.hash(state);
+        self.rules.hash(state);
+        self.cells.hash(state);
+        self.playable.hash(state);
+        self.turn_schedule.hash(state);
+        self.turn_index.hash(state);
+    }
+}
+
+/// Most positions `Board::reachable_positions` will collect
+pub const MAX_REACHABLE_POSITIONS: usize = 100_000;
+
+impl Board {
+    pub fn new(size: (usize, usize)) -> Board {
+        Board::with_rules(size, Ruleset::default())
+    }
+
+    pub fn with_rules(size: (usize, usize), rules: Ruleset) -> Board {
+        Board {
+            current_turn: 0,
+            cells: vec![None; size.0 * size.1],
+            size,
+            rules,
+            playable: None,
+            last_move: None,
+            turn_schedule: None,
+            turn_index: 0,
+        }
+    }
+
+    /// Player whose move it is
+    pub fn current_turn(&self) -> Player {
+        self.current_turn
+    }
+
+    /// Non-rectangular board, cells with `false` in the row-major `playable`
+    /// mask are holes that can't be played on and neighbor nothing
+    pub fn with_playable_mask(size: (usize, usize), playable: Vec<bool>) -> Board {
+        assert_eq!(
+            playable.len(),
+            size.0 * size.1,
+            "mask must have one entry per cell"
+        );
+
+        Board {
+            playable: Some(playable),
+            ..Board::new(size)
+        }
+    }
+
+    /// Empty board with `player` making the first move
+    pub fn new_starting_with(size: (usize, usize), player: Player) -> Board {
+        let board = Board::new(size);
+        assert!(
+            player < board.rules.player_count,
+            "starting player out of range"
+        );
+
+        Board {
+            current_turn: player,
+            ..board
+        }
+    }
+
+    /// Board of `size` set up with a named opening, `first_player` to move
+    pub fn from_start(
+        size: (usize, usize),
+        start: StartPosition,
+        first_player: Player,
+    ) -> Result<Board, GameError> {
+        if first_player >= 2 {
+            return Err(GameError::InvalidConfig("first player must be 0 or 1"));
+        }
+
+        let mut board = Board::builder(size).build()?;
+        for (coordinate, player) in start.tokens(size)? {
+            board = board.set_cell(
+                coordinate,
+                Token {
+                    player,
+                    locked: false,
+                },
+            );
+        }
+        board.current_turn = first_player;
+
+        Ok(board)
+    }
+
+    pub fn builder(size: (usize, usize)) -> BoardBuilder {
+        BoardBuilder {
+            size,
+            rules: Ruleset::default(),
+            playable: None,
+            turn_schedule: None,
+        }
+    }
+
+    /// Parses a board drawn one row per line, where `.` is an empty cell and
+    /// every other glyph must be in `legend`. Blank lines and surrounding
+    /// whitespace are ignored. The board has enough players for the legend.
+    pub fn from_ascii_art(art: &str, legend: &HashMap<char, Player>) -> Result<Board, GameError> {
+        let rows: Vec<&str> = art
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if rows.iter().any(|row| row.chars().count() != width) {
+            return Err(GameError::InvalidConfig("rows have different lengths"));
+        }
+
+        let player_count = legend
+            .values()
+            .filter(|player| **player != NEUTRAL)
+            .max()
+            .map_or(2, |max| (max + 1).max(2));
+        let mut board = Board::builder((width, rows.len()))
+            .player_count(player_count)
+            .build()?;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, glyph) in row.chars().enumerate() {
+                if glyph == '.' {
+                    continue;
+                }
+                let player = *legend.get(&glyph).ok_or(GameError::UnknownGlyph(glyph))?;
+                let index = board.cell_index(c(x, y));
+                board.cells[index] = Some(Token {
+                    player,
+                    locked: false,
+                });
+            }
+        }
+
+        Ok(board)
+    }
+
+    pub fn is_playable(&self, c: Coordinate) -> bool {
+        match &self.playable {
+            None => true,
+            Some(playable) => playable[self.cell_index(c)],
+        }
+    }
+
+    /// All moves the current player can make.
+    ///
+    /// Moves come in row-major order of the cell they start from, swaps then
+    /// in row-major order of the second cell. The solver's choice between
+    /// equally good moves depends on this order, so it must stay stable.
+    pub fn get_legal_moves(&self) -> Vec<Move> {
+        let legal_moves = self.unrestricted_moves();
+        if !self.rules.forced_scoring_swaps {
+            return legal_moves;
+        }
+
+        let points = self.count_victory_points()[self.current_turn as usize];
+        let scoring_swaps: Vec<Move> = legal_moves
+            .iter()
+            .filter(|move_| matches!(move_, Move::Swap(..)))
+            .filter(|move_| {
+                let new_state = self.advance(**move_).expect("game logic failed");
+                new_state.count_victory_points()[self.current_turn as usize] > points
+            })
+            .copied()
+            .collect();
+
+        if scoring_swaps.is_empty() {
+            legal_moves
+        } else {
+            scoring_swaps
+        }
+    }
+
+    /// Legal moves starting from `c`: a placement if it is empty, or swaps
+    /// with every other unlocked token if it holds an unlocked token
+    pub fn moves_from(&self, c: Coordinate) -> Vec<Move> {
+        self.get_legal_moves()
+            .into_iter()
+            .filter(|move_| match move_ {
+                Move::Place(coordinate) => *coordinate == c,
+                Move::Swap(pos1, _) => *pos1 == c,
+            })
+            .collect()
+    }
+
+    /// Legal moves ignoring `Ruleset::forced_scoring_swaps`
+    fn unrestricted_moves(&self) -> Vec<Move> {
+        let swappable_cells: Vec<Coordinate> = self
+            .coordinates()
+            .filter(|coordinate| self.is_swappable(*coordinate))
+            .collect();
+
+        self.get_cells()
+            .flat_map(|(cell, coordinate)| match cell {
+                None if self.placement_allowed(coordinate) => vec![Move::Place(coordinate)],
+                Some(_) if self.rules.allow_swaps && self.is_swappable(coordinate) => {
+                    swappable_cells
+                        .iter()
+                        .filter(|c| **c != coordinate)
+                        .map(|c| Move::Swap(coordinate, *c))
+                        .collect()
+                }
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    /// Whether `c` holds a token swaps may move: unlocked and not `NEUTRAL`
+    fn is_swappable(&self, c: Coordinate) -> bool {
+        self.in_bounds(c)
+            && self.cells[self.cell_index(c)]
+                .as_ref()
+                .is_some_and(|token| !token.locked && token.player != NEUTRAL)
+    }
+
+    /// Checks `Ruleset::placement` for the player to move placing on the
+    /// empty cell `c`
+    fn placement_allowed(&self, c: Coordinate) -> bool {
+        match self.rules.placement {
+            PlacementRule::Anywhere => true,
+            PlacementRule::AdjacentToOwn => {
+                let player = self.current_turn;
+                !self
+                    .cells
+                    .iter()
+                    .flatten()
+                    .any(|token| token.player == player)
+                    || self
+                        .orthogonal_neighbors(c)
+                        .any(|neighbor| self.owner(neighbor) == Some(player))
+            }
+        }
+    }
+
+    pub fn advance(&self, move_: Move) -> Result<Board, MoveError> {
+        let mut new_state = self.clone();
+        self.advance_into(move_, &mut new_state)?;
+
+        Ok(new_state)
+    }
+
+    /// `advance`, writing the new state into `dst` to reuse its allocations.
+    /// `dst` is left as it was if the move is rejected.
+    pub fn advance_into(&self, move_: Move, dst: &mut Board) -> Result<(), MoveError> {
+        self.advance_with_events(move_, dst).map(|_| ())
+    }
+
+    /// `advance_into`, also returning the cells the move locked in row-major
+    /// order, including swapped cells, so UIs can animate them
+    pub fn advance_with_events(
+        &self,
+        move_: Move,
+        dst: &mut Board,
+    ) -> Result<Vec<Coordinate>, MoveError> {
+        let mut newly_locked = Vec::new();
+        match move_ {
+            Move::Place(coordinate) => {
+                if !self.is_playable(coordinate) {
+                    return Err(MoveError::NotPlayable(coordinate));
+                }
+                if self.is_occupied(coordinate) {
+                    return Err(MoveError::Occupied(coordinate));
+                }
+
+                self.clone_into(dst);
+                dst.cells[self.cell_index(coordinate)] = Some(Token {
+                    locked: false,
+                    player: self.current_turn,
+                });
+            }
+            Move::Swap(pos1, pos2) => {
+                if pos1 == pos2 {
+                    return Err(MoveError::Illegal(move_));
+                }
+                if self.is_empty_cell(pos1) {
+                    return Err(MoveError::Empty(pos1));
+                }
+                if self.is_empty_cell(pos2) {
+                    return Err(MoveError::Empty(pos2));
+                }
+                for pos in [pos1, pos2] {
+                    if self.is_locked(pos) {
+                        return Err(MoveError::Locked(pos));
+                    }
+                    if self.owner(pos) == Some(NEUTRAL) {
+                        return Err(MoveError::Illegal(move_));
+                    }
+                }
+
+                self.clone_into(dst);
+                let index1 = self.cell_index(pos1);
+                let index2 = self.cell_index(pos2);
+                dst.cells.swap(index1, index2);
+                dst.cells[index1].as_mut().unwrap().locked = true;
+                dst.cells[index2].as_mut().unwrap().locked = true;
+                newly_locked.extend([pos1, pos2]);
+            }
+        }
+
+        newly_locked.extend(dst.update_locked_cells());
+        newly_locked.sort_by_key(|coordinate| self.cell_index(*coordinate));
+        dst.last_move = Some(move_);
+        dst.pass_turn();
+        Ok(newly_locked)
+    }
+
+    /// Hands the turn to the next player in `turn_schedule` who has a legal
+    /// move. If nobody has one, it goes to the next player anyway and the
+    /// game is over.
+    fn pass_turn(&mut self) {
+        let turns = self
+            .turn_schedule
+            .as_ref()
+            .map_or(self.rules.player_count as usize, Vec::len);
+        let before = (self.current_turn, self.turn_index);
+
+        for _ in 0..turns {
+            self.next_in_schedule();
+            // only the placement rule makes legal moves depend on the player
+            if self.rules.placement == PlacementRule::Anywhere || self.unrestricted_mobility() > 0 {
+                return;
+            }
+        }
+
+        (self.current_turn, self.turn_index) = before;
+        self.next_in_schedule();
+    }
+
+    fn next_in_schedule(&mut self) {
+        match &self.turn_schedule {
+            None => self.current_turn = (self.current_turn + 1) % self.rules.player_count,
+            Some(schedule) => {
+                self.turn_index = (self.turn_index + 1) % schedule.len();
+                self.current_turn = schedule[self.turn_index];
+            }
+        }
+    }
+
+    /// Players who would have a legal move if it was their turn, in order
+    pub fn available_players_to_move(&self) -> Vec<Player> {
+        (0..self.rules.player_count)
+            .filter(|player| {
+                Board {
+                    current_turn: *player,
+                    ..self.clone()
+                }
+                .unrestricted_mobility()
+                    > 0
+            })
+            .collect()
+    }
+
+    /// Number of `get_legal_moves`, counted without generating them unless
+    /// forced scoring swaps are on
+    pub fn mobility(&self) -> usize {
+        if self.rules.forced_scoring_swaps {
+            return self.get_legal_moves().len();
+        }
+
+        self.unrestricted_mobility()
+    }
+
+    /// Number of `unrestricted_moves`. Forced scoring swaps only ever narrow a
+    /// non-empty list, so this is zero exactly when `mobility` is
+    fn unrestricted_mobility(&self) -> usize {
+        let (empty, unlocked): (usize, usize) =
+            self.get_cells()
+                .fold((0, 0), |(empty, unlocked), (cell, coordinate)| match cell {
+                    None if self.placement_allowed(coordinate) => (empty + 1, unlocked),
+                    Some(_) if self.is_swappable(coordinate) => (empty, unlocked + 1),
+                    _ => (empty, unlocked),
+                });
+
+        if !self.rules.allow_swaps {
+            return empty;
+        }
+
+        // swaps are listed from both of their cells
+        empty + unlocked * unlocked.saturating_sub(1)
+    }
+
+    /// Checks if `move_` is one of `get_legal_moves`, without generating them
+    /// all when the rules allow
+    pub fn is_legal(&self, move_: Move) -> bool {
+        if self.rules.forced_scoring_swaps {
+            return self.get_legal_moves().contains(&move_);
+        }
+
+        match move_ {
+            Move::Place(c) => {
+                self.in_bounds(c)
+                    && self.is_playable(c)
+                    && self.is_empty_cell(c)
+                    && self.placement_allowed(c)
+            }
+            Move::Swap(pos1, pos2) => {
+                self.rules.allow_swaps
+                    && pos1 != pos2
+                    && self.is_swappable(pos1)
+                    && self.is_swappable(pos2)
+            }
+        }
+    }
+
+    /// `advance` that also requires `move_` to be one of `get_legal_moves`,
+    /// and rejects coordinates outside the board as `NotPlayable`
+    pub fn advance_strict(&self, move_: Move) -> Result<Board, MoveError> {
+        let cells = match move_ {
+            Move::Place(coordinate) => vec![coordinate],
+            Move::Swap(pos1, pos2) => vec![pos1, pos2],
+        };
+        if let Some(outside) = cells.into_iter().find(|c| !self.in_bounds(*c)) {
+            return Err(MoveError::NotPlayable(outside));
+        }
+
+        if !self.is_legal(move_) {
+            return Err(self
+                .advance(move_)
+                .err()
+                .unwrap_or(MoveError::Illegal(move_)));
+        }
+
+        self.advance(move_)
+    }
+
+    /// Strict version of `advance` for untrusted moves, only applies moves
+    /// that pass `is_legal`
+    pub fn advance_checked(&self, move_: Move) -> Result<Board, GameError> {
+        if !self.is_legal(move_) {
+            return Err(GameError::IllegalMove(move_));
+        }
+
+        Ok(self.advance(move_).expect("game logic failed"))
+    }
+
+    /// `get_legal_moves` as a JSON array for external engines, like
+    /// `[{"type":"place","x":0,"y":1},{"type":"swap","x1":0,"y1":0,"x2":1,"y2":0}]`
+    pub fn legal_moves_json(&self) -> String {
+        let moves: Vec<JsonMove> = self
+            .get_legal_moves()
+            .into_iter()
+            .map(JsonMove::from)
+            .collect();
+
+        serde_json::to_string(&moves).expect("failed to serialize moves")
+    }
+
+    /// Plays a move given in the `legal_moves_json` format, through
+    /// `advance_checked`
+    pub fn play_move_json(&self, json: &str) -> Result<Board, GameError> {
+        let move_: JsonMove = serde_json::from_str(json)?;
+
+        self.advance_checked(move_.into())
+    }
+
+    /// Plays a uniformly random legal move, or returns `None` if the game is over
+    pub fn apply_random_move(&self, rng: &mut impl Rng) -> Option<Board> {
+        if self.check_win_condition() != WinState::NotOver {
+            return None;
+        }
+        let legal_moves = self.get_legal_moves();
+        let move_ = legal_moves.choose(rng)?;
+
+        Some(self.advance(*move_).expect("game logic failed"))
+    }
+
+    /// Same position with player 0 and player 1 tokens (and turn) exchanged
+    pub fn swap_players(&self) -> Board {
+        let swap = |player: Player| match player {
+            0 => 1,
+            1 => 0,
+            other => other,
+        };
+
+        let mut new_state = self.clone();
+        for token in new_state.cells.iter_mut().flatten() {
+            token.player = swap(token.player);
+        }
+        new_state.current_turn = swap(new_state.current_turn);
+
+        new_state
+    }
+
+    /// The position reflected left to right, holes and `last_move` included
+    pub fn mirror_horizontal(&self) -> Board {
+        let width = self.size.0;
+        let mirror = |c: Coordinate| Coordinate {
+            x: width - 1 - c.x,
+            y: c.y,
+        };
+
+        let mut new_state = self.clone();
+        for coordinate in self.coordinates() {
+            let index = self.cell_index(mirror(coordinate));
+            new_state.cells[index] = self.get_cell(coordinate);
+            if let (Some(mirrored), Some(playable)) = (&mut new_state.playable, &self.playable) {
+                mirrored[index] = playable[self.cell_index(coordinate)];
+            }
+        }
+        new_state.last_move = self.last_move.map(|move_| match move_ {
+            Move::Place(c) => Move::Place(mirror(c)),
+            Move::Swap(pos1, pos2) => Move::Swap(mirror(pos1), mirror(pos2)),
+        });
+
+        new_state
+    }
+
+    /// Checks if `other` has the same size and holds this board's cells
+    /// reflected left to right. The turns and rules are not compared.
+    pub fn is_mirror_of(&self, other: &Board) -> bool {
+        self.size == other.size
+            && self.coordinates().all(|coordinate| {
+                let mirrored = c(self.size.0 - 1 - coordinate.x, coordinate.y);
+                self.get_cell(coordinate) == other.get_cell(mirrored)
+                    && self.is_playable(coordinate) == other.is_playable(mirrored)
+            })
+    }
+
+    /// `swap_players` for two-player boards: the same game with colors reversed
+    pub fn swap_colors(&self) -> Board {
+        debug_assert!(
+            self.cells.iter().flatten().all(|token| token.player < 2),
+            "swap_colors only supports two players"
+        );
+
+        self.swap_players()
+    }
+
+    /// What `advance` would change, without the caller having to diff boards
+    pub fn preview(&self, move_: Move) -> Result<PreviewResult, MoveError> {
+        let newly_locked = self.advance_with_events(move_, &mut self.clone())?;
+
+        let moved = match move_ {
+            Move::Place(coordinate) => vec![coordinate],
+            Move::Swap(pos1, pos2) => vec![pos1, pos2],
+        };
+
+        Ok(PreviewResult {
+            moved,
+            newly_locked,
+        })
+    }
+
+    /// Fraction of `samples` random playouts won by `player`, draws count as half
+    pub fn estimate_win_probability(
+        &self,
+        player: Player,
+        samples: usize,
+        rng: &mut impl Rng,
+    ) -> f64 {
+        if samples == 0 {
+            return 0.0;
+        }
+
+        let mut score = 0.0;
+        for _ in 0..samples {
+            let mut board = self.clone();
+            while let Some(new_state) = board.apply_random_move(rng) {
+                board = new_state;
+            }
+
+            score += match board.check_win_condition() {
+                WinState::Winner(winner) if winner == player => 1.0,
+                WinState::Draw => 0.5,
+                _ => 0.0,
+            };
+        }
+
+        score / samples as f64
+    }
+
+    /// Checks a puzzle whose intended solution for `player` is `solution`.
+    ///
+    /// Every move must be legal. The report tells if the solution ends in a
+    /// win for `player`, and if it is unique: no other move by `player` along
+    /// the way would still force a win in the remaining number of plies.
+    pub fn validate_puzzle(
+        &self,
+        solution: &[Move],
+        player: Player,
+    ) -> Result<PuzzleReport, GameError> {
+        let mut solver = Solver::default();
+        let mut board = self.clone();
+        let mut unique = true;
+
+        for (ply, move_) in solution.iter().enumerate() {
+            if !board.is_legal(*move_) {
+                return Err(GameError::IllegalMove(*move_));
+            }
+
+            if board.current_turn == player && unique {
+                let remaining = (solution.len() - ply - 1) as u32;
+                unique = !board
+                    .get_legal_moves()
+                    .into_iter()
+                    .filter(|alternative| alternative != move_)
+                    .any(|alternative| {
+                        let new_state = board.advance(alternative).expect("game logic failed");
+                        solver.outcome(&new_state, remaining) == Some(WinState::Winner(player))
+                    });
+            }
+
+            board = board.advance(*move_).expect("game logic failed");
+        }
+
+        Ok(PuzzleReport {
+            wins: board.check_win_condition() == WinState::Winner(player),
+            unique,
+        })
+    }
+
+    /// Shorthand for `advance(Move::place(x, y))`
+    pub fn try_place(&self, x: usize, y: usize) -> Result<Board, MoveError> {
+        self.advance(Move::place(x, y))
+    }
+
+    /// Locks every token that scores a victory point, returns the cells it locked
+    fn update_locked_cells(&mut self) -> Vec<Coordinate> {
+        let cells_to_lock: Vec<Coordinate> = self
+            .cells_and_neighbors()
+            .filter_map(|((cell, coordinate), neighbors)| {
+                if let Some(Token {
+                    player,
+                    locked: false,
+                }) = cell
+                {
+                    if self.cell_is_victory_point(neighbors, player) {
+                        Some(coordinate)
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        for coordinate in &cells_to_lock {
+            let cell_index = self.cell_index(*coordinate);
+            let token = self.cells[cell_index].as_mut().unwrap();
+            token.locked = true;
+        }
+
+        cells_to_lock
+    }
+
+    fn cell_is_victory_point(&self, neighbors: Vec<(Cell, Coordinate)>, player: Player) -> bool {
+        if player == NEUTRAL {
+            return false;
+        }
+
+        neighbors
+            .iter()
+            .filter_map(|(neighbor, _)| neighbor.as_ref())
+            .map(|token| if token.player == player { 1 } else { 0 })
+            .sum::<usize>()
+            > self.rules.victory_threshold
+    }
+
+    fn in_bounds(&self, coordinate: Coordinate) -> bool {
+        coordinate.x < self.size.0 && coordinate.y < self.size.1
+    }
+
+    fn cell_index(&self, coordinate: Coordinate) -> usize {
+        self.size.0 * coordinate.y + coordinate.x
+    }
+
+    /// Result of the game, which is over once a player reaches
+    /// `Ruleset::target_points`, there are no legal moves, or nothing left to
+    /// play could change it, see `no_progress_possible`
+    pub fn check_win_condition(&self) -> WinState {
+        if let Some(result) = self.early_result() {
+            return result;
+        }
+        if !self.get_legal_moves().is_empty() {
+            return WinState::NotOver;
+        }
+
+        self.result_by_points()
+    }
+
+    /// Result of a game that is over although moves remain: a player reached
+    /// the target, or `no_progress_possible`. The solver stops here too.
+    fn early_result(&self) -> Option<WinState> {
+        self.target_result()
+            .or_else(|| self.no_progress_possible().then(|| self.result_by_points()))
+    }
+
+    /// Checks if the result is already settled even though moves remain:
+    /// there are tokens and all of them are locked, and however the empty
+    /// cells get filled no token could start or stop scoring. Only victory
+    /// point scoring is covered, and with tied points only when ties are draws.
+    pub fn no_progress_possible(&self) -> bool {
+        if self.rules.scoring != Scoring::VictoryPoints
+            || self.cells.iter().all(Option::is_none)
+            || self
+                .cells
+                .iter()
+                .flatten()
+                .any(|token| !token.locked && token.player != NEUTRAL)
+        {
+            return false;
+        }
+        if self.rules.tie_break != TieBreak::DrawOnTie && self.result_by_points() == WinState::Draw
+        {
+            return false;
+        }
+
+        // locked tokens never move, so a cell's own neighbors can only grow
+        // by the empty cells around it getting filled
+        let could_score = |coordinate: Coordinate, player: Player| {
+            let neighbors = self.cells_neighbor_coordinates(coordinate);
+            let reachable = neighbors
+                .iter()
+                .filter(|neighbor| match &self.cells[self.cell_index(**neighbor)] {
+                    None => true,
+                    Some(token) => token.player == player,
+                })
+                .count();
+            reachable > self.rules.victory_threshold
+        };
+
+        self.get_cells().all(|(cell, coordinate)| match cell {
+            None => (0..self.rules.player_count).all(|player| !could_score(coordinate, player)),
+            Some(token) if token.player == NEUTRAL => true,
+            Some(token) => {
+                let neighbors = self.cells_neighbor_coordinates(coordinate);
+                let scores_now = self.cell_is_victory_point(
+                    neighbors
+                        .into_iter()
+                        .map(|neighbor| (self.get_cell(neighbor), neighbor))
+                        .collect(),
+                    token.player,
+                );
+                scores_now || !could_score(coordinate, token.player)
+            }
+        })
+    }
+
+    /// Winner once someone reached `Ruleset::target_points`: the one with the
+    /// highest score if several did, with ties broken by the ruleset
+    fn target_result(&self) -> Option<WinState> {
+        let target = self.rules.target_points?;
+        let scores = self.scores();
+        let best = scores
+            .iter()
+            .max()
+            .copied()
+            .filter(|best| *best >= target)?;
+        let leaders: Vec<Player> = (0..)
+            .zip(&scores)
+            .filter(|(_, score)| **score == best)
+            .map(|(player, _)| player)
+            .collect();
+
+        Some(match leaders[..] {
+            [winner] => WinState::Winner(winner),
+            _ => self.break_tie(&leaders),
+        })
+    }
+
+    /// Result of the game decided by the scores alone, with `Ruleset::komi`
+    /// applied, as if it was over
+    fn result_by_points(&self) -> WinState {
+        let mut scores: Vec<i64> = self
+            .scores()
+            .into_iter()
+            .map(|score| score as i64)
+            .collect();
+        scores[1] += self.rules.komi as i64;
+        let best = scores.iter().max().copied().unwrap_or(0);
+        let leaders: Vec<Player> = (0..)
+            .zip(&scores)
+            .filter(|(_, score)| **score == best)
+            .map(|(player, _)| player)
+            .collect();
+
+        match leaders[..] {
+            [winner] => WinState::Winner(winner),
+            _ => self.break_tie(&leaders),
+        }
+    }
+
+    /// Picks a winner among `tied` players by the ruleset's tie-break
+    fn break_tie(&self, tied: &[Player]) -> WinState {
+        let tokens_of = |player: Player| {
+            self.cells
+                .iter()
+                .flatten()
+                .filter(move |token| token.player == player)
+        };
+        let key = |player: Player| match self.rules.tie_break {
+            TieBreak::DrawOnTie => 0,
+            TieBreak::MostLocked => tokens_of(player).filter(|token| token.locked).count() as isize,
+            TieBreak::FirstToReach => -(tokens_of(player).count() as isize),
+        };
+
+        let best = tied.iter().map(|player| key(*player)).max().unwrap_or(0);
+        match tied
+            .iter()
+            .filter(|player| key(**player) == best)
+            .collect::<Vec<_>>()[..]
+        {
+            [winner] => WinState::Winner(*winner),
+            _ => WinState::Draw,
+        }
+    }
+
+    /// Score of each player under the ruleset's scoring rule
+    pub fn scores(&self) -> Vec<usize> {
+        self.rules.scoring.rule().scores(self)
+    }
+
+    /// Number of orthogonal steps from `start` to every cell, indexed like
+    /// `as_grid`. Holes are never entered, and neither are tokens unless
+    /// `through_tokens` is set. Cells that can't be reached are `None`.
+    pub fn distance_from(
+        &self,
+        start: Coordinate,
+        through_tokens: bool,
+    ) -> Vec<Vec<Option<usize>>> {
+        let mut distances = vec![vec![None; self.size.0]; self.size.1];
+        distances[start.y][start.x] = Some(0);
+
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((coordinate, distance)) = queue.pop_front() {
+            for neighbor in self.orthogonal_neighbors(coordinate) {
+                if distances[neighbor.y][neighbor.x].is_some()
+                    || (!through_tokens && self.is_occupied(neighbor))
+                {
+                    continue;
+                }
+
+                distances[neighbor.y][neighbor.x] = Some(distance + 1);
+                queue.push_back((neighbor, distance + 1));
+            }
+        }
+
+        distances
+    }
+
+    /// Distinct positions reachable in 1 to `depth` plies, nearest first.
+    /// Finished games aren't played on, and collection stops after
+    /// `MAX_REACHABLE_POSITIONS`.
+    pub fn reachable_positions(&self, depth: usize) -> Vec<Board> {
+        let mut seen = HashSet::from([self.clone()]);
+        let mut positions = Vec::new();
+        let mut frontier = vec![self.clone()];
+
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for board in &frontier {
+                if board.check_win_condition() != WinState::NotOver {
+                    continue;
+                }
+
+                for move_ in board.get_legal_moves() {
+                    if positions.len() >= MAX_REACHABLE_POSITIONS {
+                        return positions;
+                    }
+
+                    let child = board.advance(move_).expect("game logic failed");
+                    if seen.insert(child.clone()) {
+                        positions.push(child.clone());
+                        next.push(child);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        positions
+    }
+
+    /// Size of each player's largest group of orthogonally connected tokens
+    pub fn largest_groups(&self) -> Vec<usize> {
+        let mut largest = vec![0; self.rules.player_count as usize];
+        let mut visited = vec![false; self.cells.len()];
+
+        for (cell, start) in self.get_cells() {
+            let Some(token) = cell else { continue };
+            if visited[self.cell_index(start)] || token.player >= self.rules.player_count {
+                continue;
+            }
+
+            // flood fill the group `start` belongs to
+            let mut group_size = 0;
+            let mut stack = vec![start];
+            visited[self.cell_index(start)] = true;
+            while let Some(coordinate) = stack.pop() {
+                group_size += 1;
+                for neighbor in self.orthogonal_neighbors(coordinate) {
+                    let index = self.cell_index(neighbor);
+                    let same_player = self.cells[index]
+                        .as_ref()
+                        .is_some_and(|neighbor| neighbor.player == token.player);
+                    if same_player && !visited[index] {
+                        visited[index] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+
+            let player_largest = &mut largest[token.player as usize];
+            *player_largest = (*player_largest).max(group_size);
+        }
+
+        largest
+    }
+
+    pub fn count_victory_points(&self) -> Vec<usize> {
+        let mut points_per_player = Vec::new();
+
+        for player in 0..self.rules.player_count {
+            let player_points = self
+                .cells_and_neighbors()
+                .filter_map(|((cell, _), neighbors)| cell.map(|t| (t, neighbors)))
+                .filter(|(token, _)| token.player == player)
+                .filter_map(|(_, neighbors)| {
+                    self.cell_is_victory_point(neighbors, player).then_some(1)
+                })
+                .sum();
+
+            points_per_player.push(player_points);
+        }
+
+        points_per_player
+    }
+
+    /// How far player 0 is ahead in victory points, negative when behind.
+    /// On two-player boards this is their points minus player 1's.
+    pub fn margin(&self) -> i32 {
+        self.margin_for(0)
+    }
+
+    /// `player`'s victory points minus those of the best opponent
+    pub fn margin_for(&self, player: Player) -> i32 {
+        let points = self.count_victory_points();
+        let best_opponent = (0..self.rules.player_count)
+            .filter(|opponent| *opponent != player)
+            .map(|opponent| points[opponent as usize])
+            .max()
+            .expect("no opponents");
+
+        points[player as usize] as i32 - best_opponent as i32
+    }
+
+    /// Change in `player`'s victory points if the tokens at `pos1` and
+    /// `pos2` traded places, without checking that the swap is legal
+    pub fn swap_score_delta(&self, pos1: Coordinate, pos2: Coordinate, player: Player) -> i32 {
+        let mut swapped = self.clone();
+        swapped
+            .cells
+            .swap(self.cell_index(pos1), self.cell_index(pos2));
+
+        swapped.count_victory_points()[player as usize] as i32
+            - self.count_victory_points()[player as usize] as i32
+    }
+
+    /// How many neighbors of `c` belong to each player, indexed by player
+    pub fn neighbor_player_counts(&self, c: Coordinate) -> Vec<usize> {
+        let mut counts = vec![0; self.rules.player_count as usize];
+        for neighbor in self.cells_neighbor_coordinates(c) {
+            if let Some(token) = &self.cells[self.cell_index(neighbor)] {
+                if token.player == NEUTRAL {
+                    continue;
+                }
+                let player = token.player as usize;
+                if player >= counts.len() {
+                    counts.resize(player + 1, 0);
+                }
+                counts[player] += 1;
+            }
+        }
+
+        counts
+    }
+
+    /// Like `neighbor_player_counts`, but only players with neighbors are included
+    pub fn neighbor_player_histogram(&self, c: Coordinate) -> HashMap<Player, usize> {
+        let mut histogram = HashMap::new();
+        for neighbor in self.cells_neighbor_coordinates(c) {
+            match &self.cells[self.cell_index(neighbor)] {
+                Some(token) if token.player != NEUTRAL => {
+                    *histogram.entry(token.player).or_insert(0) += 1;
+                }
+                _ => {}
+            }
+        }
+
+        histogram
+    }
+
+    /// Number of empty cells where a `player` token would immediately score
+    /// a new victory point, either for itself or for one of its neighbors
+    pub fn threat_count(&self, player: Player) -> usize {
+        self.potential_victory_cells(player).len()
+    }
+
+    /// Total `threat_count` of every player other than `player`
+    pub fn opponent_threats(&self, player: Player) -> usize {
+        (0..self.rules.player_count)
+            .filter(|opponent| *opponent != player)
+            .map(|opponent| self.threat_count(opponent))
+            .sum()
+    }
+
+    /// Empty cells counted by `threat_count`, in row-major order
+    pub fn potential_victory_cells(&self, player: Player) -> Vec<Coordinate> {
+        let current_points = self.count_victory_points()[player as usize];
+
+        self.get_cells()
+            .filter(|(cell, _)| cell.is_none())
+            .map(|(_, coordinate)| coordinate)
+            .filter(|coordinate| {
+                let new_state = self.set_cell(
+                    *coordinate,
+                    Token {
+                        player,
+                        locked: false,
+                    },
+                );
+                new_state.count_victory_points()[player as usize] > current_points
+            })
+            .collect()
+    }
+
+    /// Empty cells worth highlighting for `player`: placements that would
+    /// score for them, plus placements that would deny an opponent's score.
+    /// In row-major order.
+    pub fn hotspots(&self, player: Player) -> Vec<Coordinate> {
+        let offense = self.potential_victory_cells(player);
+        let defense: Vec<Coordinate> = (0..self.rules.player_count)
+            .filter(|opponent| *opponent != player)
+            .flat_map(|opponent| self.potential_victory_cells(opponent))
+            .collect();
+
+        self.get_cells()
+            .map(|(_, coordinate)| coordinate)
+            .filter(|coordinate| offense.contains(coordinate) || defense.contains(coordinate))
+            .collect()
+    }
+
+    /// Placements for `player` creating at least two threats (see
+    /// `potential_victory_cells`) that no single opponent placement can
+    /// block, in row-major order. Opponent swaps are not considered.
+    pub fn find_forks(&self, player: Player) -> Vec<Move> {
+        let token = |player: Player| Token {
+            player,
+            locked: false,
+        };
+
+        self.get_cells()
+            .filter(|(cell, _)| cell.is_none())
+            .map(|(_, coordinate)| coordinate)
+            .filter(|coordinate| {
+                let new_state = self.set_cell(*coordinate, token(player));
+                let threats = new_state.potential_victory_cells(player);
+
+                // an opponent token elsewhere doesn't change what a threat
+                // cell would score, so blocking means taking one of them
+                threats.len() >= 2
+                    && threats.iter().all(|block| {
+                        (0..self.rules.player_count)
+                            .filter(|opponent| *opponent != player)
+                            .all(|opponent| {
+                                !new_state
+                                    .set_cell(*block, token(opponent))
+                                    .potential_victory_cells(player)
+                                    .is_empty()
+                            })
+                    })
+            })
+            .map(Move::Place)
+            .collect()
+    }
+
+    /// Empty cells next to tokens of at least two different players, in
+    /// row-major order
+    pub fn contested_cells(&self) -> Vec<Coordinate> {
+        self.get_cells()
+            .filter(|(cell, _)| cell.is_none())
+            .map(|(_, coordinate)| coordinate)
+            .filter(|coordinate| {
+                let mut players: Vec<Player> = self
+                    .cells_neighbor_coordinates(*coordinate)
+                    .into_iter()
+                    .filter_map(|neighbor| self.owner(neighbor))
+                    .filter(|player| *player != NEUTRAL)
+                    .collect();
+                players.sort_unstable();
+                players.dedup();
+                players.len() >= 2
+            })
+            .collect()
+    }
+
+    /// Rough estimate of how close `player` is to winning, in range [0, 1].
+    ///
+    /// Combines current victory points and threats (counted as half a point)
+    /// relative to the number of cells that can score at all. Meant for UI
+    /// progress bars, the solver does not use this.
+    pub fn progress(&self, player: Player) -> f32 {
+        let max_points = self
+            .get_cells()
+            .filter(|(_, coordinate)| {
+                self.cells_neighbor_coordinates(*coordinate).len() > self.rules.victory_threshold
+            })
+            .count();
+        if max_points == 0 {
+            return 0.0;
+        }
+
+        let points = self.count_victory_points()[player as usize] as f32;
+        let threats = self.threat_count(player) as f32;
+
+        ((points + threats / 2.0) / max_points as f32).min(1.0)
+    }
+
+    pub fn cells_and_neighbors(
+        &self,
+    ) -> impl Iterator<Item = ((Cell, Coordinate), Vec<(Cell, Coordinate)>)> + '_ {
+        self.get_cells()
+            .map(|(cell, coordinate)| {
+                (
+                    (cell, coordinate),
+                    self.cells_neighbor_coordinates(coordinate),
+                )
+            })
+            .map(|(cell_and_coord, neighbor_coordinates)| {
+                (
+                    cell_and_coord,
+                    neighbor_coordinates
+                        .into_iter()
+                        .map(|neighbor_coordinate| {
+                            (self.get_cell(neighbor_coordinate), neighbor_coordinate)
+                        })
+                        .collect(),
+                )
+            })
+    }
+
+    /// The cell at `c` followed by its neighbors under the ruleset, for
+    /// rules that score a cell together with its surroundings
+    pub fn region(&self, c: Coordinate) -> Vec<(Cell, Coordinate)> {
+        std::iter::once(c)
+            .chain(self.cells_neighbor_coordinates(c))
+            .map(|coordinate| (self.get_cell(coordinate), coordinate))
+            .collect()
+    }
+
+    /// How many of the neighbors of `c` are empty
+    pub fn free_neighbor_count(&self, c: Coordinate) -> usize {
+        self.cells_neighbor_coordinates(c)
+            .into_iter()
+            .filter(|neighbor| self.is_empty_cell(*neighbor))
+            .count()
+    }
+
+    /// The player owning every occupied neighbor of `c`, if there is at least
+    /// one and they all agree. Neutral tokens neighbor nobody and are skipped.
+    pub fn neighbors_uniform_player(&self, c: Coordinate) -> Option<Player> {
+        let mut owners = self
+            .cells_neighbor_coordinates(c)
+            .into_iter()
+            .filter_map(|neighbor| self.owner(neighbor))
+            .filter(|owner| *owner != NEUTRAL);
+
+        let first = owners.next()?;
+        owners.all(|owner| owner == first).then_some(first)
+    }
+
+    /// Number of cells on the board, holes included
+    pub fn total_cells(&self) -> usize {
+        self.size.0 * self.size.1
+    }
+
+    /// Fraction of `total_cells` holding a token
+    pub fn fill_ratio(&self) -> f32 {
+        self.cells.iter().flatten().count() as f32 / self.total_cells() as f32
+    }
+
+    /// Number of locked tokens on the board
+    pub fn locked_count(&self) -> usize {
+        self.cells
+            .iter()
+            .flatten()
+            .filter(|token| token.locked)
+            .count()
+    }
+
+    /// Fraction of the tokens on the board that are locked, 0 without tokens
+    pub fn locked_ratio(&self) -> f32 {
+        let tokens = self.cells.iter().flatten().count();
+        if tokens == 0 {
+            return 0.0;
+        }
+
+        self.locked_count() as f32 / tokens as f32
+    }
+
+    /// Every coordinate of the board in row-major order, holes included
+    pub fn coordinates(&self) -> impl Iterator<Item = Coordinate> {
+        let (width, height) = self.size;
+        (0..height).flat_map(move |y| (0..width).map(move |x| c(x, y)))
+    }
+
+    fn get_cells(&self) -> impl Iterator<Item = (Cell, Coordinate)> + '_ {
+        self.coordinates()
+            .filter(|c| self.is_playable(*c))
+            .map(|c| (self.get_cell(c), c))
+    }
+
+    /// Zobrist hash of the position: the XOR of a pseudo-random key for every
+    /// token and for the player to move. Not affected by `last_move`.
+    pub fn hash_key(&self) -> u64 {
+        let mut key = zobrist_key(u64::MAX, self.current_turn as u64);
+        if self.turn_schedule.is_some() {
+            key ^= zobrist_key(u64::MAX - 1, self.turn_index as u64);
+        }
+        for (index, cell) in self.cells.iter().enumerate() {
+            if let Some(token) = cell {
+                key ^= zobrist_key(
+                    index as u64,
+                    (token.player as u64) << 1 | token.locked as u64,
+                );
+            }
+        }
+
+        key
+    }
+
+    /// Hash of what `hash_key` leaves out although grading depends on it:
+    /// the size, ruleset and playable mask
+    fn variant_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.size, self.rules, &self.playable).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Single-line text form of the position for logs, like
+    /// `3x2/t0/x.O/_.#`: size, player to move, then the rows. Cells use the
+    /// `Display` glyphs with `_` for holes. Only players 0 and 1 and
+    /// neutral tokens are supported, and the ruleset is not included.
+    pub fn compact(&self) -> String {
+        let rows: Vec<String> = (0..self.size.1)
+            .map(|y| {
+                (0..self.size.0)
+                    .map(|x| match self.get_cell(c(x, y)) {
+                        None if !self.is_playable(c(x, y)) => '_',
+                        None => '.',
+                        Some(token) => {
+                            assert!(
+                                token.player < 2 || token.player == NEUTRAL,
+                                "compact only supports two players"
+                            );
+                            token.to_string().chars().next().expect("token has a glyph")
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        format!(
+            "{}x{}/t{}/{}",
+            self.size.0,
+            self.size.1,
+            self.current_turn,
+            rows.join("/")
+        )
+    }
+
+    /// Parses a board written by `compact`, with the default ruleset
+    pub fn from_compact(compact: &str) -> Result<Board, GameError> {
+        let mut parts = compact.split('/');
+        let (width, height) = parts
+            .next()
+            .and_then(|size| size.split_once('x'))
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .ok_or(GameError::Corrupt("invalid size"))?;
+        let current_turn: Player = parts
+            .next()
+            .and_then(|turn| turn.strip_prefix('t'))
+            .and_then(|turn| turn.parse().ok())
+            .filter(|turn| *turn < 2)
+            .ok_or(GameError::Corrupt("invalid current turn"))?;
+        let rows: Vec<&str> = parts.collect();
+        if rows.len() != height || rows.iter().any(|row| row.chars().count() != width) {
+            return Err(GameError::Corrupt("rows don't match the size"));
+        }
+
+        let mut cells = Vec::with_capacity(width * height);
+        let mut playable = Vec::with_capacity(width * height);
+        for glyph in rows.iter().flat_map(|row| row.chars()) {
+            let (player, locked) = match glyph {
+                '_' | '.' => {
+                    cells.push(None);
+                    playable.push(glyph == '.');
+                    continue;
+                }
+                'x' | 'X' => (0, glyph == 'X'),
+                'o' | 'O' => (1, glyph == 'O'),
+                '#' => (NEUTRAL, false),
+                // neutral tokens were written as `h` before they got their own glyph
+                'h' | 'H' => (NEUTRAL, glyph == 'H'),
+                _ => return Err(GameError::UnknownGlyph(glyph)),
+            };
+            cells.push(Some(Token { player, locked }));
+            playable.push(true);
+        }
+
+        let mut builder = Board::builder((width, height));
+        if playable.contains(&false) {
+            builder = builder.playable_mask(playable);
+        }
+        let mut board = builder.build()?;
+        board.cells = cells;
+        board.current_turn = current_turn;
+
+        Ok(board)
+    }
+
+    /// Compact binary encoding of the position, for two-player boards.
+    ///
+    /// Layout: width and height as little-endian `u16`, current turn as `u8`,
+    /// then 2 bits per cell (0 = empty, 1 = player 0, 2 = player 1,
+    /// 3 = neutral) followed by a bitmap of locked cells. The ruleset and playable mask are not
+    /// included.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        assert!(
+            self.size.0 <= u16::MAX as usize && self.size.1 <= u16::MAX as usize,
+            "to_bytes only supports board sides up to u16::MAX"
+        );
+        let cell_count = self.cells.len();
+        let mut bytes =
+            Vec::with_capacity(BYTES_HEADER_LEN + cell_count.div_ceil(4) + cell_count.div_ceil(8));
+        bytes.extend_from_slice(&(self.size.0 as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.size.1 as u16).to_le_bytes());
+        bytes.push(self.current_turn as u8);
+
+        let mut players = vec![0u8; cell_count.div_ceil(4)];
+        let mut locks = vec![0u8; cell_count.div_ceil(8)];
+        for (index, cell) in self.cells.iter().enumerate() {
+            if let Some(token) = cell {
+                let code = match token.player {
+                    NEUTRAL => 3,
+                    player => {
+                        assert!(player < 2, "to_bytes only supports two players");
+                        player as u8 + 1
+                    }
+                };
+                players[index / 4] |= code << (index % 4 * 2);
+                if token.locked {
+                    locks[index / 8] |= 1 << (index % 8);
+                }
+            }
+        }
+        bytes.extend(players);
+        bytes.extend(locks);
+
+        bytes
+    }
+
+    /// Decodes a board written by `to_bytes`, with the default ruleset
+    pub fn from_bytes(bytes: &[u8]) -> Result<Board, GameError> {
+        let header = bytes.get(..BYTES_HEADER_LEN).ok_or(GameError::Truncated)?;
+        let width = u16::from_le_bytes([header[0], header[1]]) as usize;
+        let height = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let current_turn = header[4] as Player;
+        if width == 0 || height == 0 {
+            return Err(GameError::Corrupt("board has no cells"));
+        }
+        if current_turn > 1 {
+            return Err(GameError::Corrupt("invalid current turn"));
+        }
+
+        let cell_count = width * height;
+        let players_len = cell_count.div_ceil(4);
+        let body = &bytes[BYTES_HEADER_LEN..];
+        if body.len() < players_len + cell_count.div_ceil(8) {
+            return Err(GameError::Truncated);
+        }
+        if body.len() > players_len + cell_count.div_ceil(8) {
+            return Err(GameError::Corrupt("trailing data"));
+        }
+        let (players, locks) = body.split_at(players_len);
+
+        let mut board = Board::new((width, height));
+        board.current_turn = current_turn;
+        for index in 0..cell_count {
+            let locked = locks[index / 8] & (1 << (index % 8)) != 0;
+            board.cells[index] = match (players[index / 4] >> (index % 4 * 2)) & 0b11 {
+                0 if locked => return Err(GameError::Corrupt("empty cell is locked")),
+                0 => None,
+                3 => Some(Token {
+                    player: NEUTRAL,
+                    locked,
+                }),
+                player => Some(Token {
+                    player: player as Player - 1,
+                    locked,
+                }),
+            };
+        }
+
+        Ok(board)
+    }
+
+    /// Parses a board in its serde form, checking that the parts agree with
+    /// each other so a bad board is an error rather than a panic later on
+    pub fn from_json(json: &str) -> Result<Board, GameError> {
+        let board: Board = serde_json::from_str(json)?;
+        board.check_consistency()?;
+
+        Ok(board)
+    }
+
+    /// Checks the invariants that deserializing can't, see `from_json`
+    fn check_consistency(&self) -> Result<(), GameError> {
+        let cell_count = self
+            .size
+            .0
+            .checked_mul(self.size.1)
+            .ok_or(GameError::Corrupt("board is too large"))?;
+        if cell_count == 0 {
+            return Err(GameError::Corrupt("board has no cells"));
+        }
+        if self.cells.len() != cell_count {
+            return Err(GameError::Corrupt("cells don't match the size"));
+        }
+        self.rules.check(self.size).map_err(GameError::Corrupt)?;
+        if self
+            .playable
+            .as_ref()
+            .is_some_and(|playable| playable.len() != cell_count)
+        {
+            return Err(GameError::Corrupt("playable mask doesn't match the size"));
+        }
+        if self.current_turn >= self.rules.player_count {
+            return Err(GameError::Corrupt("invalid current turn"));
+        }
+        if let Some(schedule) = &self.turn_schedule {
+            if schedule.get(self.turn_index) != Some(&self.current_turn) {
+                return Err(GameError::Corrupt("turn schedule doesn't match the turn"));
+            }
+            if schedule
+                .iter()
+                .any(|player| *player >= self.rules.player_count)
+            {
+                return Err(GameError::Corrupt("invalid player in turn schedule"));
+            }
+        }
+        if self
+            .cells
+            .iter()
+            .flatten()
+            .any(|token| token.player >= self.rules.player_count && token.player != NEUTRAL)
+        {
+            return Err(GameError::Corrupt("invalid player on a cell"));
+        }
+
+        Ok(())
+    }
+
+    /// Cells that differ between two boards of the same size, as
+    /// `(coordinate, cell in self, cell in other)` in row-major order.
+    /// After a move this is the moved cells plus any that got locked.
+    pub fn diff(&self, other: &Board) -> Vec<(Coordinate, Cell, Cell)> {
+        assert_eq!(
+            self.size, other.size,
+            "can only diff boards of the same size"
+        );
+
+        self.get_cells()
+            .filter_map(|(cell, coordinate)| {
+                let other_cell = other.get_cell(coordinate);
+                (cell != other_cell).then_some((coordinate, cell, other_cell))
+            })
+            .collect()
+    }
+
+    fn set_cell(&self, coordinate: Coordinate, token: Token) -> Board {
+        self.with_cell(coordinate, Some(token))
+    }
+
+    /// Copy of the board with an unlocked `NEUTRAL` token at `c`, for setting
+    /// up obstacles before the game starts
+    pub fn place_neutral(&self, c: Coordinate) -> Board {
+        self.with_cell(
+            c,
+            Some(Token {
+                player: NEUTRAL,
+                locked: false,
+            }),
+        )
+    }
+
+    /// Copy of the board with the cell at `c` replaced by `cell`. This is a
+    /// raw edit for setting up positions: it bypasses the rules, so nothing
+    /// gets locked and the turn does not change.
+    pub fn with_cell(&self, c: Coordinate, cell: Cell) -> Board {
+        let mut new_state = self.clone();
+        new_state.cells[self.cell_index(c)] = cell;
+
+        new_state
+    }
+
+    /// Raw edit of the lock on the token at `c`, like `with_cell`
+    pub fn set_locked(&mut self, c: Coordinate, locked: bool) -> Result<(), MoveError> {
+        if !self.in_bounds(c) || !self.is_playable(c) {
+            return Err(MoveError::NotPlayable(c));
+        }
+
+        let index = self.cell_index(c);
+        let token = self.cells[index].as_mut().ok_or(MoveError::Empty(c))?;
+        token.locked = locked;
+
+        Ok(())
+    }
+
+    /// Whether `c` holds a locked token
+    pub fn is_locked(&self, c: Coordinate) -> bool {
+        self.in_bounds(c)
+            && self.cells[self.cell_index(c)]
+                .as_ref()
+                .is_some_and(|token| token.locked)
+    }
+
+    fn get_cell(&self, c: Coordinate) -> Cell {
+        self.cells[self.cell_index(c)].clone()
+    }
+
+    /// Cells as rows, so that `as_grid()[y][x]` is the cell at `(x, y)`
+    pub fn as_grid(&self) -> Vec<Vec<Cell>> {
+        self.rows().collect()
+    }
+
+    /// One view per playable cell in row-major order, for GUI frontends to
+    /// map onto widgets
+    pub fn render_cells(&self) -> Vec<CellView> {
+        self.get_cells()
+            .map(|(cell, coordinate)| CellView {
+                coordinate,
+                player: cell.as_ref().map(|token| token.player),
+                locked: cell.is_some_and(|token| token.locked),
+            })
+            .collect()
+    }
+
+    /// Canonical multi-line dump of the position for snapshot tests, e.g.
+    /// `assert_eq!(board.snapshot(), expected)` with the expected text kept
+    /// next to the test. Lists the size, turn, grid (`_` for holes), victory
+    /// points per player and the locked cells in row-major order.
+    pub fn snapshot(&self) -> String {
+        use std::fmt::Write;
+
+        let mut snapshot = String::new();
+        writeln!(snapshot, "size: {}x{}", self.size.0, self.size.1).unwrap();
+        writeln!(snapshot, "turn: {}", self.current_turn).unwrap();
+
+        writeln!(snapshot, "grid:").unwrap();
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                match self.get_cell(c(x, y)) {
+                    None if !self.is_playable(c(x, y)) => snapshot.push('_'),
+                    None => snapshot.push('.'),
+                    Some(token) => write!(snapshot, "{token}").unwrap(),
+                }
+            }
+            snapshot.push('\n');
+        }
+
+        let points: Vec<String> = self
+            .count_victory_points()
+            .iter()
+            .map(|points| points.to_string())
+            .collect();
+        writeln!(snapshot, "points: {}", points.join(" ")).unwrap();
+
+        let locked: Vec<String> = self
+            .coordinates()
+            .filter(|coordinate| self.is_locked(*coordinate))
+            .map(|coordinate| format!("{},{}", coordinate.x, coordinate.y))
+            .collect();
+        writeln!(snapshot, "locked: {}", locked.join(" ")).unwrap();
+
+        snapshot
+    }
+
+    /// Cells of each row, top to bottom, `size.1` of them
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = Vec<Cell>> + '_ {
+        self.cells.chunks(self.size.0).map(|row| row.to_vec())
+    }
+
+    /// Cells of each column, left to right, `size.0` of them
+    pub fn columns(&self) -> impl ExactSizeIterator<Item = Vec<Cell>> + '_ {
+        (0..self.size.0).map(|x| {
+            self.cells
+                .iter()
+                .skip(x)
+                .step_by(self.size.0)
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// The move `advance` played to reach this board, for highlighting it in UIs
+    pub fn last_move(&self) -> Option<Move> {
+        self.last_move
+    }
+
+    /// Players with at least one token on the board, sorted. Neutral tokens
+    /// don't count.
+    pub fn players_present(&self) -> Vec<Player> {
+        let mut players: Vec<Player> = self
+            .cells
+            .iter()
+            .flatten()
+            .map(|token| token.player)
+            .filter(|player| *player != NEUTRAL)
+            .collect();
+        players.sort_unstable();
+        players.dedup();
+
+        players
+    }
+
+    /// Checks if there is a token at `c`, without cloning it
+    pub fn is_occupied(&self, c: Coordinate) -> bool {
+        self.cells[self.cell_index(c)].is_some()
+    }
+
+    /// Player whose token is at `c`, `None` if it is empty or out of bounds
+    pub fn owner(&self, c: Coordinate) -> Option<Player> {
+        if !self.in_bounds(c) {
+            return None;
+        }
+
+        self.cells[self.cell_index(c)]
+            .as_ref()
+            .map(|token| token.player)
+    }
+
+    pub fn is_empty_cell(&self, c: Coordinate) -> bool {
+        !self.is_occupied(c)
+    }
+
+    /// The up to 4 playable cells next to `c`, regardless of the ruleset
+    fn orthogonal_neighbors(&self, c: Coordinate) -> impl Iterator<Item = Coordinate> + '_ {
+        [(0, -1), (-1, 0), (1, 0), (0, 1)]
+            .into_iter()
+            .filter_map(move |(dx, dy)| self.offset(c, dx, dy))
+            .filter(move |neighbor| *neighbor != c)
+    }
+
+    /// Playable cells other than `c` within Manhattan distance `r` of it,
+    /// regardless of the ruleset's radius, top to bottom. Wraps around the
+    /// edges if the rules say so.
+    pub fn cells_within_manhattan(&self, c: Coordinate, r: usize) -> Vec<Coordinate> {
+        let r = r as isize;
+
+        let mut cells = Vec::new();
+        for dy in -r..=r {
+            let reach = r - dy.abs();
+            for dx in -reach..=reach {
+                if let Some(cell) = self.offset(c, dx, dy) {
+                    // small wrapping boards can reach the same cell twice
+                    if cell != c && !cells.contains(&cell) {
+                        cells.push(cell);
+                    }
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Playable cell at `(dx, dy)` from `c`, wrapping around the edges if the
+    /// rules say so
+    fn offset(&self, c: Coordinate, dx: isize, dy: isize) -> Option<Coordinate> {
+        let target = if self.rules.wrap {
+            Coordinate {
+                x: (c.x as isize + dx).rem_euclid(self.size.0 as isize) as usize,
+                y: (c.y as isize + dy).rem_euclid(self.size.1 as isize) as usize,
+            }
+        } else {
+            Coordinate {
+                x: c.x.checked_add_signed(dx)?,
+                y: c.y.checked_add_signed(dy)?,
+            }
+        };
+
+        (self.in_bounds(target) && self.is_playable(target)).then_some(target)
+    }
+
+    fn cells_neighbor_coordinates(&self, cell_coordinates: Coordinate) -> Vec<Coordinate> {
+        let radius = self.rules.radius as isize;
+
+        let mut neighbors = Vec::new();
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let distance = self
+                    .rules
+                    .distance
+                    .of_offset(dx.unsigned_abs(), dy.unsigned_abs());
+                if distance == 0 || distance > self.rules.radius {
+                    continue;
+                }
+
+                // on small wrapping boards several offsets can land on the same cell
+                if let Some(neighbor) = self.offset(cell_coordinates, dx, dy) {
+                    if neighbor != cell_coordinates && !neighbors.contains(&neighbor) {
+                        neighbors.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+}
+
+/// Pseudo-random key for a feature of a position, mixed with splitmix64 so
+/// no key table needs to be stored
+fn zobrist_key(index: u64, feature: u64) -> u64 {
+    let mut z = index
+        .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        .wrapping_add(feature.wrapping_mul(0xbf58_476d_1ce4_e5b9))
+        .wrapping_add(0x94d0_49bb_1331_11eb);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Size and turn header of `Board::to_bytes`
+const BYTES_HEADER_LEN: usize = 5;
+
+/// Planes in `Board::to_feature_planes`
+#[cfg(feature = "ndarray")]
+const FEATURE_PLANES: usize = 4;
+
+#[cfg(feature = "ndarray")]
+impl Board {
+    /// One-hot encoding of the position as `(plane, y, x)` for training
+    /// evaluators: player 0's tokens, player 1's tokens, locked tokens, and
+    /// a plane of ones when player 1 is to move. Holes are all zero.
+    pub fn to_feature_planes(&self) -> ndarray::Array3<f32> {
+        let (width, height) = self.size;
+        let mut planes = ndarray::Array3::zeros((FEATURE_PLANES, height, width));
+
+        for (cell, coordinate) in self.get_cells() {
+            let (x, y) = (coordinate.x, coordinate.y);
+            if let Some(token) = cell {
+                if token.player < 2 {
+                    planes[[token.player as usize, y, x]] = 1.0;
+                }
+                if token.locked {
+                    planes[[2, y, x]] = 1.0;
+                }
+            }
+            if self.current_turn == 1 {
+                planes[[3, y, x]] = 1.0;
+            }
+        }
+
+        planes
+    }
+}
+
+impl Board {
+    fn write_grid(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                match self.get_cell(c(x, y)) {
+                    None if !self.is_playable(c(x, y)) => write!(f, " ")?,
+                    None => write!(f, ".")?,
+                    Some(token) => write!(f, "{token}")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, " - Board: (current turn: {})", self.current_turn)?;
+        self.write_grid(f)
+    }
+}
+
+/// Shows the grid instead of the raw cell vector
+impl std::fmt::Debug for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Board {{ size: {:?}, current_turn: {}, rules: {:?} }}",
+            self.size, self.current_turn, self.rules
+        )?;
+        self.write_grid(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MoveError {
+    /// Tried to place on a cell that already has a token
+    Occupied(Coordinate),
+    /// Tried to swap a cell that has no token
+    Empty(Coordinate),
+    /// Tried to place in a hole of a masked board
+    NotPlayable(Coordinate),
+    /// Tried to swap a locked token
+    Locked(Coordinate),
+    /// The move breaks the rules in some other way, e.g. swapping a cell with
+    /// itself, see also `Board::advance_strict`
+    Illegal(Move),
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::Occupied(at) => write!(f, "cell ({}, {}) is occupied", at.x, at.y),
+            MoveError::Empty(at) => write!(f, "cell ({}, {}) is empty", at.x, at.y),
+            MoveError::NotPlayable(at) => {
+                write!(f, "cell ({}, {}) is not part of the board", at.x, at.y)
+            }
+            MoveError::Locked(at) => write!(f, "cell ({}, {}) is locked", at.x, at.y),
+            MoveError::Illegal(move_) => write!(f, "{move_:?} is not a legal move"),
+        }
+    }
+}
+
+/// Result of `Board::validate_puzzle`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PuzzleReport {
+    /// The solution ends in a win for the puzzle's player
+    pub wins: bool,
+    /// No deviation by the puzzle's player also forces a win
+    pub unique: bool,
+}
+
+/// What a GUI needs to draw one cell, see `Board::render_cells`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CellView {
+    pub coordinate: Coordinate,
+    /// Owner of the token on the cell, `None` if it is empty
+    pub player: Option<Player>,
+    pub locked: bool,
+}
+
+/// Effects of a move, see `Board::preview`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PreviewResult {
+    /// Cells the move places a token on or swaps
+    pub moved: Vec<Coordinate>,
+    /// Cells that would become locked, including swapped cells
+    pub newly_locked: Vec<Coordinate>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Move {
+    Place(Coordinate),
+    Swap(Coordinate, Coordinate),
+}
+
+impl Move {
+    pub fn place(x: usize, y: usize) -> Move {
+        Move::Place(c(x, y))
+    }
+
+    pub fn swap(pos1: (usize, usize), pos2: (usize, usize)) -> Move {
+        Move::Swap(c(pos1.0, pos1.1), c(pos2.0, pos2.1))
+    }
+}
+
+/// `Move` as spoken to external engines, see `Board::legal_moves_json`.
+/// Saves keep using the `Move` format.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum JsonMove {
+    Place {
+        x: usize,
+        y: usize,
+    },
+    Swap {
+        x1: usize,
+        y1: usize,
+        x2: usize,
+        y2: usize,
+    },
+}
+
+impl From<Move> for JsonMove {
+    fn from(move_: Move) -> Self {
+        match move_ {
+            Move::Place(Coordinate { x, y }) => JsonMove::Place { x, y },
+            Move::Swap(pos1, pos2) => JsonMove::Swap {
+                x1: pos1.x,
+                y1: pos1.y,
+                x2: pos2.x,
+                y2: pos2.y,
+            },
+        }
+    }
+}
+
+impl From<JsonMove> for Move {
+    fn from(move_: JsonMove) -> Self {
+        match move_ {
+            JsonMove::Place { x, y } => Move::place(x, y),
+            JsonMove::Swap { x1, y1, x2, y2 } => Move::swap((x1, y1), (x2, y2)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GameError {
+    Parse(serde_json::Error),
+    UnsupportedVersion(u32),
+    IllegalMove(Move),
+    /// Binary data ended before the board did
+    Truncated,
+    Corrupt(&'static str),
+    /// Board options that don't work together
+    InvalidConfig(&'static str),
+    /// A character that doesn't stand for any cell in a text board
+    UnknownGlyph(char),
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::Parse(error) => write!(f, "failed to parse JSON: {error}"),
+            GameError::UnsupportedVersion(version) => {
+                write!(f, "unsupported save format version {version}")
+            }
+            GameError::IllegalMove(move_) => write!(f, "illegal move: {move_:?}"),
+            GameError::Truncated => write!(f, "board data is truncated"),
+            GameError::Corrupt(reason) => write!(f, "board data is corrupt: {reason}"),
+            GameError::InvalidConfig(reason) => write!(f, "invalid board configuration: {reason}"),
+            GameError::UnknownGlyph(glyph) => write!(f, "unknown board glyph {glyph:?}"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for GameError {
+    fn from(error: serde_json::Error) -> Self {
+        GameError::Parse(error)
+    }
+}
+
+/// Upgrades a save from version `i + 1` to `i + 2`, filling in defaults for
+/// whatever that version added. The current format version is always one
+/// past the last migration.
+const SAVE_MIGRATIONS: &[fn(&mut serde_json::Value)] = &[migrate_v1_rules];
+
+pub const SAVE_FORMAT_VERSION: u32 = SAVE_MIGRATIONS.len() as u32 + 1;
+
+/// Version 2 added the ruleset to the board
+fn migrate_v1_rules(save: &mut serde_json::Value) {
+    let rules = serde_json::to_value(Ruleset::default()).expect("failed to serialize rules");
+    if let Some(start) = save
+        .get_mut("start")
+        .and_then(|start| start.as_object_mut())
+    {
+        start.entry("rules").or_insert(rules);
+    }
+}
+
+/// On-disk representation of a `Game`
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    start: Board,
+    moves: Vec<Move>,
+    #[serde(default)]
+    resigned: Option<Player>,
+    #[serde(default)]
+    timed_out: Option<Player>,
+}
+
+/// How a `Game` ended, or that it is still going
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GameEnd {
+    /// Decided on the board, see `Board::check_win_condition`
+    Scored(WinState),
+    /// The player conceded, and their opponent won
+    Resignation { loser: Player },
+    /// The player ran out of time in `Game::play_match`
+    Timeout { loser: Player },
+}
+
+impl GameEnd {
+    /// Winning player in a two-player game, if the game is over and not a draw
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameEnd::Scored(WinState::Winner(winner)) => Some(*winner),
+            GameEnd::Scored(_) => None,
+            GameEnd::Resignation { loser } | GameEnd::Timeout { loser } => Some((loser + 1) % 2),
+        }
+    }
+}
+
+/// A board together with the moves played on it
+#[derive(Debug, Clone)]
+pub struct Game {
+    start: Board,
+    board: Board,
+    moves: Vec<Move>,
+    /// Thinking time for each of `moves`, zero for moves not chosen by a controller
+    move_times: Vec<Duration>,
+    /// Resignation or timeout that ended the game, regardless of the position
+    forfeit: Option<GameEnd>,
+}
+
+impl Game {
+    pub fn new(start: Board) -> Game {
+        Game {
+            board: start.clone(),
+            start,
+            moves: Vec::new(),
+            move_times: Vec::new(),
+            forfeit: None,
+        }
+    }
+
+    /// Concedes the game for `player`, regardless of the position
+    pub fn resign(&mut self, player: Player) {
+        self.forfeit
+            .get_or_insert(GameEnd::Resignation { loser: player });
+    }
+
+    pub fn outcome(&self) -> GameEnd {
+        self.forfeit
+            .unwrap_or_else(|| GameEnd::Scored(self.board.check_win_condition()))
+    }
+
+    /// The move that clinched the game, as its index into `moves`: the first
+    /// one after which the solver finds a forced win for the eventual winner.
+    /// `None` for draws, for resignations that were never forced and for
+    /// boards too large to solve.
+    pub fn decisive_move(&self) -> Option<(usize, Move)> {
+        let winner = self.outcome().winner()?;
+        let mut solver = Solver::default();
+
+        let mut board = self.start.clone();
+        for (index, move_) in self.moves.iter().enumerate() {
+            board = board.advance(*move_).expect("game holds an illegal move");
+            let (score, _) = solver.find_best_move_scored(&board, winner).ok()?;
+            if is_decisive(score) && score > 0 {
+                return Some((index, *move_));
+            }
+        }
+
+        None
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Every position of the game in order, from `start` to the current
+    /// board, for viewers to step through
+    pub fn frames(&self) -> Vec<Board> {
+        let mut frames = Vec::with_capacity(self.moves.len() + 1);
+        frames.push(self.start.clone());
+        for move_ in &self.moves {
+            let previous = frames.last().expect("frames start with the start position");
+            frames.push(
+                previous
+                    .advance(*move_)
+                    .expect("game holds an illegal move"),
+            );
+        }
+
+        frames
+    }
+
+    /// How long the controller took to choose each move in `moves`
+    pub fn move_times(&self) -> &[Duration] {
+        &self.move_times
+    }
+
+    pub fn play(&mut self, move_: Move) -> Result<(), MoveError> {
+        self.play_timed(move_, Duration::ZERO)
+    }
+
+    fn play_timed(&mut self, move_: Move, elapsed: Duration) -> Result<(), MoveError> {
+        self.board = self.board.advance(move_)?;
+        self.moves.push(move_);
+        self.move_times.push(elapsed);
+
+        Ok(())
+    }
+
+    /// Plays the game to the end, asking `controllers[player]` for each move.
+    ///
+    /// With a clock, each move's thinking time is charged to the player who
+    /// made it and running out of time loses the game.
+    pub fn play_match(
+        &mut self,
+        controllers: &mut [&mut dyn Controller],
+        mut clock: Option<&mut Clock>,
+    ) -> GameEnd {
+        loop {
+            let outcome = self.outcome();
+            if outcome != GameEnd::Scored(WinState::NotOver) {
+                return outcome;
+            }
+
+            let player = self.board.current_turn;
+            let started = Instant::now();
+            let action = controllers[player as usize].choose_action(&self.board);
+            let elapsed = started.elapsed();
+
+            if let Some(clock) = clock.as_deref_mut() {
+                if !clock.charge(player, elapsed) {
+                    self.forfeit = Some(GameEnd::Timeout { loser: player });
+                    continue;
+                }
+            }
+
+            match action {
+                ControllerAction::Play(move_) => self
+                    .play_timed(move_, elapsed)
+                    .expect("controller played an illegal move"),
+                ControllerAction::Resign => self.resign(player),
+            }
+        }
+    }
+
+    pub fn save(&self) -> String {
+        let save = SaveFile {
+            version: SAVE_FORMAT_VERSION,
+            start: self.start.clone(),
+            moves: self.moves.clone(),
+            resigned: match self.forfeit {
+                Some(GameEnd::Resignation { loser }) => Some(loser),
+                _ => None,
+            },
+            timed_out: match self.forfeit {
+                Some(GameEnd::Timeout { loser }) => Some(loser),
+                _ => None,
+            },
+        };
+
+        serde_json::to_string(&save).expect("failed to serialize game")
+    }
+
+    /// Loads a game saved by `save`, upgrading saves from older versions.
+    ///
+    /// The current position is rebuilt by replaying the moves, so a save
+    /// containing illegal moves is rejected.
+    pub fn load(save: &str) -> Result<Game, GameError> {
+        let mut value: serde_json::Value = serde_json::from_str(save)?;
+
+        let version = value
+            .get("version")
+            .and_then(|version| version.as_u64())
+            .unwrap_or(1) as u32;
+        if version == 0 || version > SAVE_FORMAT_VERSION {
+            return Err(GameError::UnsupportedVersion(version));
+        }
+
+        for migration in &SAVE_MIGRATIONS[version as usize - 1..] {
+            migration(&mut value);
+        }
+        if let Some(save) = value.as_object_mut() {
+            save.insert("version".into(), SAVE_FORMAT_VERSION.into());
+        }
+
+        let save: SaveFile = serde_json::from_value(value)?;
+        save.start.check_consistency()?;
+        let mut game = Game::new(save.start);
+        for move_ in save.moves {
+            game.play(move_)
+                .map_err(|_| GameError::IllegalMove(move_))?;
+        }
+        game.forfeit = save
+            .resigned
+            .map(|loser| GameEnd::Resignation { loser })
+            .or(save.timed_out.map(|loser| GameEnd::Timeout { loser }));
+
+        Ok(game)
+    }
+}
+
+/// Largest board (in cells) we are willing to build a tablebase for. A 3x3
+/// table holds about 650 000 positions and takes seconds to build.
+pub const MAX_TABLEBASE_CELLS: usize = MAX_SOLVABLE_CELLS;
+
+/// Exact scores of every position reachable from the empty board, see
+/// `build_tablebase`
+///
+/// Only feasible for tiny boards, see `MAX_TABLEBASE_CELLS`
+#[derive(Debug, Clone)]
+pub struct Tablebase {
+    scores: HashMap<Board, i32>,
+}
+
+impl Tablebase {
+    pub fn build(size: (usize, usize)) -> Tablebase {
+        Tablebase {
+            scores: build_tablebase(size),
+        }
+    }
+
+    /// Score of `board` for the player to move, if it is in the table
+    pub fn score(&self, board: &Board) -> Option<i32> {
+        self.scores.get(board).copied()
+    }
+
+    /// Value of `board` with perfect play from both sides
+    ///
+    /// Panics if `board` can't be reached from the empty board of the
+    /// table's size, check with `score` if that is possible
+    pub fn probe(&self, board: &Board) -> WinState {
+        let player = board.current_turn;
+        let score = self
+            .score(board)
+            .expect("position is not reachable from the table's empty board");
+
+        match score.signum() {
+            1 => WinState::Winner(player),
+            -1 => WinState::Winner((player + 1) % 2),
+            _ => WinState::Draw,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+}
+
+/// Exact score of every position reachable from the empty board of `size`,
+/// for the player to move and scored like `Solver::find_best_move_scored`: 0
+/// for a draw, `WIN_SCORE` less the plies to go for a forced win and the
+/// negation of that for a forced loss.
+///
+/// Plain minimax over the whole game, each position solved once.
+pub fn build_tablebase(size: (usize, usize)) -> HashMap<Board, i32> {
+    assert!(
+        size.0 * size.1 <= MAX_TABLEBASE_CELLS,
+        "board too large for a tablebase"
+    );
+
+    let mut scores = HashMap::new();
+    solve_all(&Board::new(size), &mut scores);
+
+    scores
+}
+
+/// Score of `board` for the player to move, adding it and every position
+/// reachable from it to `scores`
+fn solve_all(board: &Board, scores: &mut HashMap<Board, i32>) -> i32 {
+    if let Some(score) = scores.get(board) {
+        return *score;
+    }
+
+    let player = board.current_turn;
+    let legal_moves = board.get_legal_moves();
+    let score = match board.early_result() {
+        Some(result) => terminal_score(result, player),
+        None if legal_moves.is_empty() => terminal_score(board.result_by_points(), player),
+        None => legal_moves
+            .into_iter()
+            .map(|move_| {
+                let new_state = board.advance(move_).expect("game logic failed");
+                let score = solve_all(&new_state, scores);
+                if new_state.current_turn == player {
+                    add_ply(score)
+                } else {
+                    add_ply(-score)
+                }
+            })
+            .max()
+            .expect("position has legal moves"),
+    };
+
+    scores.insert(board.clone(), score);
+    score
+}
+
+/// Cache that evicts the least recently used entry when full.
+///
+/// A capacity of 0 disables caching.
+#[derive(Debug, Clone)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+    clock: u64,
+    entries: HashMap<K, (V, u64)>,
+    /// Keys by when they were last used
+    usage: BTreeMap<u64, K>,
+}
+
+impl<K: Eq + Hash + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> LruCache<K, V> {
+        LruCache {
+            capacity,
+            clock: 0,
+            entries: HashMap::with_capacity(capacity),
+            usage: BTreeMap::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let (_, last_used) = self.entries.get_mut(key)?;
+        self.usage.remove(last_used);
+        self.clock += 1;
+        *last_used = self.clock;
+        self.usage.insert(self.clock, key.clone());
+
+        self.entries.get(key).map(|(value, _)| value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some((_, last_used)) = self.entries.remove(&key) {
+            self.usage.remove(&last_used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.usage.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.usage.insert(self.clock, key.clone());
+        self.entries.insert(key, (value, self.clock));
+    }
+}
+
+impl<K, V> Default for LruCache<K, V> {
+    fn default() -> Self {
+        LruCache {
+            capacity: 0,
+            clock: 0,
+            entries: HashMap::new(),
+            usage: BTreeMap::new(),
+        }
+    }
+}
+
+/// Counters collected while searching
+#[derive(Debug, Clone, Default)]
+pub struct SearchStats {
+    /// How many times legal moves were generated, cache hits excluded
+    pub move_generations: usize,
+    /// How many positions were graded, cache hits excluded
+    pub grade_calls: usize,
+    /// How many positions `Solver::search` visited, transposition hits excluded
+    pub nodes: usize,
+}
+
+/// A position in `Solver::search_tree` with its minimax score
+#[derive(Debug, Clone)]
+pub struct SearchNode {
+    pub board: Board,
+    pub score: i32,
+    /// One child per legal move, empty at the depth limit and at game end
+    pub children: Vec<(Move, SearchNode)>,
+}
+
+/// Result of one completed depth of `Solver::find_best_move_iterative`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SearchProgress {
+    pub depth: u32,
+    pub best_move: Option<Move>,
+    pub score: i32,
+    /// Positions visited so far, over all depths
+    pub nodes: usize,
+}
+
+/// Called by the solver after each completed depth, see
+/// `Solver::set_progress_callback`
+pub struct ProgressCallback(Box<dyn FnMut(&SearchProgress)>);
+
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// What `Solver::negamax` found for a position
+#[derive(Debug, Clone, Copy)]
+pub struct TranspositionEntry {
+    /// Remaining depth the position was searched with, `None` is unlimited
+    depth: Option<u32>,
+    /// Best move found, `None` at leaves
+    best_move: Option<Move>,
+    /// For the player to move
+    score: i32,
+    bound: Bound,
+}
+
+/// How a `TranspositionEntry` score relates to the true minimax value
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Bound {
+    Exact,
+    /// The search was cut off, the value is at least the score
+    Lower,
+    /// No move reached alpha, the value is at most the score
+    Upper,
+}
+
+/// Shards in a `SharedTT`, so threads storing different positions rarely
+/// wait on each other
+const SHARED_TT_SHARDS: usize = 16;
+
+/// Transposition table for several threads searching the same tree, see
+/// `Solver::find_best_move_parallel`. Keyed by `Board::hash_key`, and split
+/// into mutex-guarded shards.
+#[derive(Debug)]
+pub struct SharedTT {
+    shards: Vec<Mutex<HashMap<u64, TranspositionEntry>>>,
+}
+
+impl SharedTT {
+    pub fn new() -> SharedTT {
+        SharedTT {
+            shards: (0..SHARED_TT_SHARDS).map(|_| Mutex::default()).collect(),
+        }
+    }
+
+    fn shard(&self, key: u64) -> &Mutex<HashMap<u64, TranspositionEntry>> {
+        &self.shards[key as usize % self.shards.len()]
+    }
+
+    pub fn get(&self, key: u64) -> Option<TranspositionEntry> {
+        self.shard(key).lock().unwrap().get(&key).copied()
+    }
+
+    /// Stores `entry` unless the position was already searched deeper
+    pub fn store(&self, key: u64, entry: TranspositionEntry) {
+        let mut shard = self.shard(key).lock().unwrap();
+        match shard.get(&key) {
+            Some(existing) if !covers_depth(entry.depth, existing.depth) => {}
+            _ => {
+                shard.insert(key, entry);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards
+            .iter()
+            .all(|shard| shard.lock().unwrap().is_empty())
+    }
+}
+
+impl Default for SharedTT {
+    fn default() -> Self {
+        SharedTT::new()
+    }
+}
+
+/// Score of winning on the spot in `Solver::find_best_move_scored`. A win
+/// `n` plies away scores `WIN_SCORE - n`, so quicker wins and slower losses
+/// are preferred.
+pub const WIN_SCORE: i32 = 1_000_000;
+
+/// Scores beyond this (either way) are forced wins or losses, any grade is
+/// far below it
+const WIN_THRESHOLD: i32 = WIN_SCORE / 2;
+
+/// Bounds the alpha-beta window, beyond any real score
+const SCORE_INFINITY: i32 = WIN_SCORE + 1;
+
+/// Checks if `score` is a forced win or loss rather than a grade
+pub fn is_decisive(score: i32) -> bool {
+    score.abs() > WIN_THRESHOLD
+}
+
+/// A child's score as seen from its parent: a forced win or loss is one ply
+/// further away
+fn add_ply(score: i32) -> i32 {
+    if score > WIN_THRESHOLD {
+        score - 1
+    } else if score < -WIN_THRESHOLD {
+        score + 1
+    } else {
+        score
+    }
+}
+
+/// Inverse of `add_ply`, for passing alpha-beta bounds down to a child
+fn remove_ply(bound: i32) -> i32 {
+    if bound > WIN_THRESHOLD {
+        bound + 1
+    } else if bound < -WIN_THRESHOLD {
+        bound - 1
+    } else {
+        bound
+    }
+}
+
+/// Score of a finished game for `player`
+fn terminal_score(result: WinState, player: Player) -> i32 {
+    match result {
+        WinState::Winner(winner) if winner == player => WIN_SCORE,
+        WinState::Winner(_) => -WIN_SCORE,
+        WinState::Draw | WinState::NotOver => 0,
+    }
+}
+
+/// Checks if a search to depth `searched` also answers a search to `wanted`
+fn covers_depth(searched: Option<u32>, wanted: Option<u32>) -> bool {
+    match (searched, wanted) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(searched), Some(wanted)) => searched >= wanted,
+    }
+}
+
+/// Largest board (in cells) the solver will search exhaustively. Anything
+/// bigger needs a depth limit.
+pub const MAX_SOLVABLE_CELLS: usize = 9;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SolverError {
+    /// The board is too large to solve without a depth limit
+    TooLarge { cells: usize },
+}
+
+impl Display for SolverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverError::TooLarge { cells } => write!(
+                f,
+                "board has {cells} cells, more than {MAX_SOLVABLE_CELLS} can be solved exhaustively; set a depth limit"
+            ),
+        }
+    }
+}
+
+/// Searches for good moves.
+///
+/// The solver is deterministic: it uses no randomness and tries moves in
+/// `Board::get_legal_moves` order, and its caches only ever skip work, so
+/// the same board and settings always give the same move.
+#[derive(Debug, Default)]
+pub struct Solver {
+    /// How many plies `find_best_move` looks ahead, `None` searches to the end
+    depth_limit: Option<u32>,
+    tablebase: Option<Tablebase>,
+    move_cache: LruCache<Board, Vec<Move>>,
+    /// Grades by `Board::hash_key` mixed with `Board::variant_key`, and the
+    /// player they are for. Kept across searches, so boards of other games
+    /// must not share keys.
+    eval_cache: LruCache<(u64, Player), i32>,
+    /// Results of `outcome`, with the depth they were searched to
+    outcome_cache: HashMap<Board, (u32, Option<WinState>)>,
+    /// Results of `negamax` during the current `find_best_move`
+    transpositions: HashMap<Board, TranspositionEntry>,
+    /// Used instead of `transpositions` by `find_best_move_parallel` workers
+    shared_transpositions: Option<Arc<SharedTT>>,
+    /// Extra penalty in `grade` for each opponent threat, see `with_threat_denial`
+    threat_denial: i32,
+    progress: Option<ProgressCallback>,
+    /// Set during `search_cancellable`, abandons the search once raised
+    cancel: Option<Arc<AtomicBool>>,
+    /// Set when a depth-limited search graded a position instead of playing
+    /// it out, so `search_cancellable` knows if going deeper can change anything
+    horizon_reached: bool,
+    stats: SearchStats,
+}
+
+impl Solver {
+    pub fn with_tablebase(tablebase: Tablebase) -> Solver {
+        Solver {
+            tablebase: Some(tablebase),
+            ..Default::default()
+        }
+    }
+
+    /// Solver which remembers the legal moves of up to `capacity` positions
+    pub fn with_move_cache(capacity: usize) -> Solver {
+        Solver {
+            move_cache: LruCache::new(capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Solver which remembers the grades of up to `capacity` positions
+    pub fn with_eval_cache(capacity: usize) -> Solver {
+        Solver {
+            eval_cache: LruCache::new(capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Solver which grades every threat of any opponent `weight` lower, so
+    /// `heuristic_move` prefers blocking them over building its own
+    pub fn with_threat_denial(weight: i32) -> Solver {
+        Solver {
+            threat_denial: weight,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_depth_limit(depth_limit: u32) -> Solver {
+        Solver {
+            depth_limit: Some(depth_limit),
+            ..Default::default()
+        }
+    }
+
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    /// Finds the best move for `player` within the depth limit, assuming
+    /// every other player answers with the moves worst for `player`.
+    ///
+    /// Without a depth limit, boards larger than `MAX_SOLVABLE_CELLS` are
+    /// refused, as solving them exhaustively would practically never finish.
+    pub fn find_best_move(
+        &mut self,
+        board: &Board,
+        player: u32,
+    ) -> Result<Option<Move>, SolverError> {
+        Ok(self.find_best_move_scored(board, player)?.1)
+    }
+
+    /// `find_best_move` together with its score for `player`: `WIN_SCORE`
+    /// less the plies to go for a forced win, the negation of that for a
+    /// forced loss, otherwise the `grade` of the position at the depth limit.
+    pub fn find_best_move_scored(
+        &mut self,
+        board: &Board,
+        player: u32,
+    ) -> Result<(i32, Option<Move>), SolverError> {
+        let cells = board.total_cells();
+        if self.depth_limit.is_none() && cells > MAX_SOLVABLE_CELLS {
+            return Err(SolverError::TooLarge { cells });
+        }
+
+        self.transpositions.clear();
+        Ok(self.search(board, player, self.depth_limit))
+    }
+
+    /// `find_best_move_scored` with the root moves shared out between
+    /// `threads` threads, which cooperate through a `SharedTT`. Picks the
+    /// same move as the sequential search.
+    pub fn find_best_move_parallel(
+        &mut self,
+        board: &Board,
+        player: u32,
+        threads: usize,
+    ) -> Result<(i32, Option<Move>), SolverError> {
+        let legal_moves = self.legal_moves(board);
+        if threads <= 1
+            || legal_moves.is_empty()
+            || board.early_result().is_some()
+            || self.depth_limit == Some(0)
+        {
+            return self.find_best_move_scored(board, player);
+        }
+
+        let cells = board.total_cells();
+        if self.depth_limit.is_none() && cells > MAX_SOLVABLE_CELLS {
+            return Err(SolverError::TooLarge { cells });
+        }
+
+        let shared = Arc::new(SharedTT::new());
+        let next_move = AtomicUsize::new(0);
+        let child_depth = self.depth_limit.map(|depth| depth - 1);
+        let (depth_limit, threat_denial) = (self.depth_limit, self.threat_denial);
+        let tablebase = self.tablebase.as_ref();
+
+        let mut scores: Vec<(usize, i32)> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    let (shared, next_move, legal_moves) = (&shared, &next_move, &legal_moves);
+                    scope.spawn(move || {
+                        let mut worker = Solver {
+                            depth_limit,
+                            tablebase: tablebase.cloned(),
+                            threat_denial,
+                            shared_transpositions: Some(Arc::clone(shared)),
+                            ..Default::default()
+                        };
+
+                        let mut scores = Vec::new();
+                        loop {
+                            let index = next_move.fetch_add(1, Ordering::Relaxed);
+                            let Some(move_) = legal_moves.get(index) else {
+                                break;
+                            };
+                            let new_state = board.advance(*move_).expect("game logic failed");
+                            let score = worker.child_score(
+                                &new_state,
+                                board.current_turn,
+                                child_depth,
+                                -SCORE_INFINITY,
+                                SCORE_INFINITY,
+                            );
+                            scores.push((index, score));
+                        }
+
+                        (scores, worker.stats.nodes)
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| {
+                    let (scores, nodes) = worker.join().expect("search thread panicked");
+                    self.stats.nodes += nodes;
+                    scores
+                })
+                .collect()
+        });
+
+        // first of the best moves in move order, like `negamax_node`
+        scores.sort_unstable_by_key(|(index, _)| *index);
+        let (best_index, best_score) =
+            scores
+                .into_iter()
+                .fold((0, -SCORE_INFINITY), |best, (index, score)| {
+                    if score > best.1 {
+                        (index, score)
+                    } else {
+                        best
+                    }
+                });
+
+        let score = if board.current_turn == player {
+            best_score
+        } else {
+            -best_score
+        };
+        Ok((score, Some(legal_moves[best_index])))
+    }
+
+    /// Every move sharing the best `find_best_move_scored` score, in
+    /// `Board::get_legal_moves` order, together with that score
+    pub fn best_moves(
+        &mut self,
+        board: &Board,
+        player: u32,
+    ) -> Result<(i32, Vec<Move>), SolverError> {
+        let cells = board.total_cells();
+        if self.depth_limit.is_none() && cells > MAX_SOLVABLE_CELLS {
+            return Err(SolverError::TooLarge { cells });
+        }
+
+        self.transpositions.clear();
+        let legal_moves = self.legal_moves(board);
+        if legal_moves.is_empty() || board.early_result().is_some() || self.depth_limit == Some(0) {
+            return Ok((self.search(board, player, Some(0)).0, Vec::new()));
+        }
+
+        let depth = self.depth_limit.map(|depth| depth - 1);
+        let mut best_score = -SCORE_INFINITY;
+        let mut best_moves = Vec::new();
+        for move_ in legal_moves {
+            let new_state = board.advance(move_).expect("game logic failed");
+            let score = self.child_score(
+                &new_state,
+                board.current_turn,
+                depth,
+                -SCORE_INFINITY,
+                SCORE_INFINITY,
+            );
+            if score > best_score {
+                best_score = score;
+                best_moves.clear();
+            }
+            if score == best_score {
+                best_moves.push(move_);
+            }
+        }
+
+        if board.current_turn != player {
+            best_score = -best_score;
+        }
+        Ok((best_score, best_moves))
+    }
+
+    /// Searches one ply deeper at a time up to `max_depth`, reporting each
+    /// completed depth to the progress callback. Stops early once the game
+    /// is decided either way.
+    pub fn find_best_move_iterative(
+        &mut self,
+        board: &Board,
+        player: u32,
+        max_depth: u32,
+    ) -> (i32, Option<Move>) {
+        self.transpositions.clear();
+        let nodes_before = self.stats.nodes;
+
+        let mut best = (0, None);
+        for depth in 1..=max_depth {
+            best = self.search(board, player, Some(depth));
+
+            if let Some(ProgressCallback(callback)) = &mut self.progress {
+                callback(&SearchProgress {
+                    depth,
+                    best_move: best.1,
+                    score: best.0,
+                    nodes: self.stats.nodes - nodes_before,
+                });
+            }
+            if is_decisive(best.0) {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// Searches one ply deeper at a time until `cancel` is raised, the game
+    /// is decided or solved to the end, or the depth limit (if any) is
+    /// reached. Returns the result of the deepest completed search, or the
+    /// first legal move if cancelled before the first one finished.
+    pub fn search_cancellable(
+        &mut self,
+        board: &Board,
+        player: u32,
+        cancel: Arc<AtomicBool>,
+    ) -> (i32, Option<Move>) {
+        self.cancel = Some(cancel);
+
+        let first_move = self.legal_moves(board).first().copied();
+        let mut best = (self.evaluate(board, player), first_move);
+        for depth in 1.. {
+            if self.depth_limit.is_some_and(|limit| depth > limit) {
+                break;
+            }
+
+            // entries from shallower depths could hide that this one was
+            // cut off at the horizon
+            self.transpositions.clear();
+            self.horizon_reached = false;
+            let result = self.search(board, player, Some(depth));
+            if self.cancelled() {
+                break;
+            }
+            best = result;
+            if is_decisive(best.0) || !self.horizon_reached {
+                break;
+            }
+        }
+
+        self.cancel = None;
+        best
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// Sets the callback `find_best_move_iterative` reports each completed
+    /// depth to, for showing the solver's progress
+    pub fn set_progress_callback(&mut self, callback: impl FnMut(&SearchProgress) + 'static) {
+        self.progress = Some(ProgressCallback(Box::new(callback)));
+    }
+
+    /// The line of play `find_best_move` expects, with best play from every
+    /// side, as far as the search looked
+    pub fn principal_variation(
+        &mut self,
+        board: &Board,
+        player: u32,
+    ) -> Result<Vec<Move>, SolverError> {
+        self.find_best_move(board, player)?;
+
+        let mut line = Vec::new();
+        let mut board = board.clone();
+        while let Some(TranspositionEntry {
+            best_move: Some(move_),
+            ..
+        }) = self.transpositions.get(&board)
+        {
+            line.push(*move_);
+            board = board.advance(*move_).expect("game logic failed");
+        }
+
+        Ok(line)
+    }
+
+    /// Best move on `board` and its `negamax` score, turned around to be
+    /// from `player`'s point of view
+    fn search(&mut self, board: &Board, player: u32, depth: Option<u32>) -> (i32, Option<Move>) {
+        let score = self.negamax(board, depth, -SCORE_INFINITY, SCORE_INFINITY);
+        let best_move = self.transposition(board).and_then(|entry| entry.best_move);
+
+        if board.current_turn == player {
+            (score, best_move)
+        } else {
+            (-score, best_move)
+        }
+    }
+
+    /// Alpha-beta search scored for the player to move. Every other player
+    /// is treated as their opponent, so a position is worth the negation of
+    /// the best reply.
+    fn negamax(&mut self, board: &Board, depth: Option<u32>, mut alpha: i32, beta: i32) -> i32 {
+        if let Some(entry) = self.transposition(board) {
+            if covers_depth(entry.depth, depth) {
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                };
+                if usable {
+                    return entry.score;
+                }
+            }
+        }
+
+        self.stats.nodes += 1;
+        let original_alpha = alpha;
+        let (score, best_move) = self.negamax_node(board, depth, &mut alpha, beta);
+        if self.cancelled() {
+            // the score is meaningless, keep it out of the table
+            return score;
+        }
+
+        let bound = if score <= original_alpha {
+            Bound::Upper
+        } else if score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.store_transposition(
+            board,
+            TranspositionEntry {
+                depth,
+                best_move,
+                score,
+                bound,
+            },
+        );
+
+        score
+    }
+
+    /// `negamax` of the position after one of `player`'s moves, scored for
+    /// `player`. Negated unless the turn schedule lets them move again.
+    fn child_score(
+        &mut self,
+        new_state: &Board,
+        player: Player,
+        depth: Option<u32>,
+        alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        let (alpha, beta) = (remove_ply(alpha), remove_ply(beta));
+        let score = if new_state.current_turn == player {
+            self.negamax(new_state, depth, alpha, beta)
+        } else {
+            -self.negamax(new_state, depth, -beta, -alpha)
+        };
+
+        add_ply(score)
+    }
+
+    fn transposition(&self, board: &Board) -> Option<TranspositionEntry> {
+        match &self.shared_transpositions {
+            Some(shared) => shared.get(board.hash_key()),
+            None => self.transpositions.get(board).copied(),
+        }
+    }
+
+    fn store_transposition(&mut self, board: &Board, entry: TranspositionEntry) {
+        match &self.shared_transpositions {
+            Some(shared) => shared.store(board.hash_key(), entry),
+            None => {
+                self.transpositions.insert(board.clone(), entry);
+            }
+        }
+    }
+
+    fn negamax_node(
+        &mut self,
+        board: &Board,
+        depth: Option<u32>,
+        alpha: &mut i32,
+        beta: i32,
+    ) -> (i32, Option<Move>) {
+        let player = board.current_turn;
+        if let Some(result) = board.early_result() {
+            return (terminal_score(result, player), None);
+        }
+        let legal_moves = self.legal_moves(board);
+        if legal_moves.is_empty() {
+            return (terminal_score(board.result_by_points(), player), None);
+        }
+        // the table holds every position reachable from this one, so a move
+        // whose child keeps the score the table gives this position is a
+        // best move. Only boards from other games are searched
+        if let Some(tablebase) = &self.tablebase {
+            if let Some(score) = tablebase.score(board) {
+                let best_move = legal_moves.iter().copied().find(|move_| {
+                    let new_state = board.advance(*move_).expect("game logic failed");
+                    let child_score = tablebase
+                        .score(&new_state)
+                        .expect("tablebase is missing a reachable position");
+                    let child_score = if new_state.current_turn == player {
+                        child_score
+                    } else {
+                        -child_score
+                    };
+                    add_ply(child_score) == score
+                });
+                return (score, best_move);
+            }
+        }
+
+        if depth == Some(0) {
+            self.horizon_reached = true;
+            return (self.evaluate(board, player), None);
+        }
+
+        #[cfg(feature = "trace")]
+        let _span =
+            tracing::trace_span!("node", turn = player, moves = legal_moves.len()).entered();
+
+        let mut best_score = -SCORE_INFINITY;
+        let mut best_move = None;
+        let mut new_state = board.clone();
+        for move_ in legal_moves {
+            board
+                .advance_into(move_, &mut new_state)
+                .expect("game logic failed");
+            let score = self.child_score(
+                &new_state,
+                player,
+                depth.map(|depth| depth - 1),
+                *alpha,
+                beta,
+            );
+            #[cfg(feature = "trace")]
+            tracing::trace!(?move_, score, "searched move");
+
+            if self.cancelled() {
+                break;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(move_);
+            }
+            *alpha = (*alpha).max(score);
+            if *alpha >= beta {
+                #[cfg(feature = "trace")]
+                tracing::trace!(?move_, alpha, beta, "cutoff");
+                break;
+            }
+        }
+
+        (best_score, best_move)
+    }
+
+    /// Every position within `depth` plies of `board`, scored like
+    /// `find_best_move_scored` for the player to move on `board`. Holds the
+    /// whole tree in memory without pruning, so keep `depth` small.
+    pub fn search_tree(&self, board: &Board, depth: u32) -> SearchNode {
+        self.tree_node(board, board.current_turn, depth)
+    }
+
+    fn tree_node(&self, board: &Board, player: Player, depth: u32) -> SearchNode {
+        let legal_moves = board.get_legal_moves();
+        let result = match board.early_result() {
+            Some(result) => Some(result),
+            None if legal_moves.is_empty() => Some(board.result_by_points()),
+            None => None,
+        };
+        if let Some(result) = result {
+            return SearchNode {
+                board: board.clone(),
+                score: terminal_score(result, player),
+                children: Vec::new(),
+            };
+        }
+        if depth == 0 {
+            return SearchNode {
+                board: board.clone(),
+                score: self.grade_for(board, player),
+                children: Vec::new(),
+            };
+        }
+
+        let children: Vec<(Move, SearchNode)> = legal_moves
+            .into_iter()
+            .map(|move_| {
+                let new_state = board.advance(move_).expect("game logic failed");
+                (move_, self.tree_node(&new_state, player, depth - 1))
+            })
+            .collect();
+        let scores = children.iter().map(|(_, child)| add_ply(child.score));
+        let score = if board.current_turn == player {
+            scores.max()
+        } else {
+            scores.min()
+        };
+
+        SearchNode {
+            board: board.clone(),
+            score: score.expect("position has legal moves"),
+            children,
+        }
+    }
+
+    /// Checks if best play from both sides leads to a draw, with neither side
+    /// able to force a win within `depth` plies
+    pub fn is_forced_draw(&mut self, board: &Board, depth: u32) -> bool {
+        self.outcome_cache.clear();
+
+        self.outcome(board, depth) == Some(WinState::Draw)
+    }
+
+    /// Result of `board` with best play from both sides, or `None` if it is
+    /// not decided within `depth` plies
+    fn outcome(&mut self, board: &Board, depth: u32) -> Option<WinState> {
+        match self.outcome_cache.get(board) {
+            Some((_, Some(result))) => return Some(*result),
+            Some((searched, None)) if *searched >= depth => return None,
+            _ => {}
+        }
+
+        let legal_moves = self.legal_moves(board);
+        let result = if let Some(result) = board.early_result() {
+            Some(result)
+        } else if legal_moves.is_empty() {
+            Some(board.result_by_points())
+        } else if depth == 0 {
+            None
+        } else {
+            let player = board.current_turn;
+            let mut best = Some(WinState::Winner((player + 1) % 2));
+            for move_ in legal_moves {
+                let new_state = board.advance(move_).expect("game logic failed");
+                match self.outcome(&new_state, depth - 1) {
+                    Some(WinState::Winner(winner)) if winner == player => {
+                        best = Some(WinState::Winner(player));
+                        break;
+                    }
+                    Some(WinState::Draw) => {
+                        if best.is_some() {
+                            best = Some(WinState::Draw);
+                        }
+                    }
+                    Some(_) => {}
+                    None => best = None,
+                }
+            }
+            best
+        };
+
+        self.outcome_cache.insert(board.clone(), (depth, result));
+        result
+    }
+
+    fn legal_moves(&mut self, board: &Board) -> Vec<Move> {
+        if let Some(legal_moves) = self.move_cache.get(board) {
+            return legal_moves.clone();
+        }
+
+        self.stats.move_generations += 1;
+        let legal_moves = board.get_legal_moves();
+        if self.move_cache.capacity() > 0 {
+            self.move_cache.insert(board.clone(), legal_moves.clone());
+        }
+
+        legal_moves
+    }
+
+    /// `grade_for`, cached by the position's Zobrist key
+    fn evaluate(&mut self, board: &Board, player: Player) -> i32 {
+        let key = (board.hash_key() ^ board.variant_key(), player);
+        if let Some(grade) = self.eval_cache.get(&key) {
+            return *grade;
+        }
+
+        self.stats.grade_calls += 1;
+        let grade = self.grade_for(board, player);
+        self.eval_cache.insert(key, grade);
+
+        grade
+    }
+
+    /// The legal move whose resulting position grades best for the player
+    /// making it, looking a single ply ahead. `None` if the game is over.
+    pub fn heuristic_move(&mut self, board: &Board) -> Option<Move> {
+        let player = board.current_turn;
+        let mut next = board.clone();
+        let mut best: Option<(i32, Move)> = None;
+        for move_ in self.legal_moves(board) {
+            board
+                .advance_into(move_, &mut next)
+                .expect("game logic failed");
+            self.stats.grade_calls += 1;
+            let grade = self.grade_for(&next, player);
+            if best.is_none_or(|(best_grade, _)| grade > best_grade) {
+                best = Some((grade, move_));
+            }
+        }
+
+        best.map(|(_, move_)| move_)
+    }
+
+    /// Heuristic value of `board` for the player to move, higher is better.
+    ///
+    /// Victory points are worth the most, threats break ties.
+    pub fn grade(&self, board: &Board) -> i32 {
+        self.grade_for(board, board.current_turn)
+    }
+
+    /// Sanity check for `grade` on two-player boards: with the colors
+    /// swapped but the same seat to move, the grade should be negated.
+    ///
+    /// A `with_threat_denial` weight deliberately breaks this.
+    pub fn grade_is_antisymmetric(&self, board: &Board) -> bool {
+        // `swap_colors` hands the turn over too, and `grade` is relative to
+        // the player to move
+        let mut swapped = board.swap_colors();
+        swapped.current_turn = board.current_turn;
+
+        self.grade(board) == -self.grade(&swapped)
+    }
+
+    /// `grade` from the point of view of `player`, whether or not it is
+    /// their turn
+    fn grade_for(&self, board: &Board, player: Player) -> i32 {
+        let points = board.count_victory_points();
+        let opponent = (0..board.rules.player_count)
+            .filter(|opponent| *opponent != player)
+            .max_by_key(|opponent| points[*opponent as usize])
+            .expect("no opponents");
+
+        let point_margin = points[player as usize] as i32 - points[opponent as usize] as i32;
+        let threat_margin = board.threat_count(player) as i32 - board.threat_count(opponent) as i32;
+
+        point_margin * 10 + threat_margin
+            - self.threat_denial * board.opponent_threats(player) as i32
+    }
+}
+
+/// What a `Controller` does on its turn
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ControllerAction {
+    Play(Move),
+    /// Concede the game, see `Game::resign`
+    Resign,
+}
+
+/// Picks moves for one seat in `Game::play_match`
+pub trait Controller {
+    /// Only called when `board` has legal moves
+    fn choose_action(&mut self, board: &Board) -> ControllerAction;
+}
+
+impl Controller for Solver {
+    /// Plays `find_best_move`
+    fn choose_action(&mut self, board: &Board) -> ControllerAction {
+        let move_ = self
+            .find_best_move(board, board.current_turn)
+            .expect("board too large for the solver")
+            .expect("no legal moves");
+
+        ControllerAction::Play(move_)
+    }
+}
+
+/// Chance of a random move instead of the solver's in `generate_selfplay`
+const SELFPLAY_EXPLORATION: f64 = 0.2;
+
+/// Positions from solver self-play, each labelled with its search score for
+/// the player to move, as training data for evaluation models.
+///
+/// Games start from an empty board of `size`, searched `depth` plies deep.
+/// Some moves are picked at random by `rng` so the games differ; with the
+/// same seed the data is the same.
+pub fn generate_selfplay(
+    size: (usize, usize),
+    games: usize,
+    depth: u32,
+    rng: &mut impl Rng,
+) -> Vec<(Board, i32)> {
+    let mut solver = Solver::with_depth_limit(depth);
+    let mut positions = Vec::new();
+
+    for _ in 0..games {
+        let mut board = Board::new(size);
+        loop {
+            let (score, best_move) = solver
+                .find_best_move_scored(&board, board.current_turn)
+                .expect("search is depth limited");
+            let Some(best_move) = best_move else { break };
+            positions.push((board.clone(), score));
+
+            let move_ = if rng.gen_bool(SELFPLAY_EXPLORATION) {
+                *board
+                    .get_legal_moves()
+                    .choose(rng)
+                    .expect("game is not over")
+            } else {
+                best_move
+            };
+            board = board.advance(move_).expect("game logic failed");
+        }
+    }
+
+    positions
+}
+
+/// Plays uniformly random legal moves, a baseline to measure other
+/// controllers against
+#[derive(Debug, Clone)]
+pub struct RandomController {
+    rng: StdRng,
+}
+
+impl RandomController {
+    pub fn new(seed: u64) -> RandomController {
+        RandomController {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Controller for RandomController {
+    fn choose_action(&mut self, board: &Board) -> ControllerAction {
+        let legal_moves = board.get_legal_moves();
+        let move_ = legal_moves.choose(&mut self.rng).expect("no legal moves");
+
+        ControllerAction::Play(*move_)
+    }
+}
+
+/// Results of many games between the same controllers, see `Tournament::run`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Tournament {
+    games: usize,
+    /// Games won by each seat
+    wins: Vec<usize>,
+    draws: usize,
+}
+
+impl Tournament {
+    /// Plays `games` games from `start`, spread over `threads` threads.
+    ///
+    /// `make_controllers` sets up one controller per seat for each game,
+    /// from an RNG seeded by `seed` and the game's index, so the results
+    /// only depend on `seed` and not on how the games were scheduled.
+    /// `on_game` is called from the worker threads with the number of games
+    /// finished so far and how the latest one ended.
+    pub fn run(
+        start: &Board,
+        games: usize,
+        threads: usize,
+        seed: u64,
+        make_controllers: impl Fn(&mut StdRng) -> Vec<Box<dyn Controller>> + Sync,
+        on_game: impl Fn(usize, GameEnd) + Sync,
+    ) -> Tournament {
+        let threads = threads.clamp(1, games.max(1));
+        let finished = AtomicUsize::new(0);
+
+        let ends: Vec<GameEnd> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|thread| {
+                    let (make_controllers, on_game, finished) =
+                        (&make_controllers, &on_game, &finished);
+                    scope.spawn(move || {
+                        (thread..games)
+                            .step_by(threads)
+                            .map(|game| {
+                                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(game as u64));
+                                let mut controllers = make_controllers(&mut rng);
+                                let mut seats: Vec<&mut dyn Controller> = controllers
+                                    .iter_mut()
+                                    .map(|controller| &mut **controller as &mut dyn Controller)
+                                    .collect();
+
+                                let end = Game::new(start.clone()).play_match(&mut seats, None);
+                                on_game(finished.fetch_add(1, Ordering::Relaxed) + 1, end);
+                                (game, end)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            let mut ends = vec![GameEnd::Scored(WinState::NotOver); games];
+            for worker in workers {
+                for (game, end) in worker.join().expect("tournament game panicked") {
+                    ends[game] = end;
+                }
+            }
+            ends
+        });
+
+        let mut tournament = Tournament {
+            games,
+            wins: vec![0; start.rules.player_count as usize],
+            draws: 0,
+        };
+        for end in ends {
+            match end.winner() {
+                Some(winner) => tournament.wins[winner as usize] += 1,
+                None => tournament.draws += 1,
+            }
+        }
+
+        tournament
+    }
+
+    /// Fraction of games won by `player`
+    pub fn win_rate(&self, player: Player) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+
+        self.wins[player as usize] as f64 / self.games as f64
+    }
+
+    /// 95% Wilson score interval of `player`'s true win rate
+    pub fn confidence_interval(&self, player: Player) -> (f64, f64) {
+        if self.games == 0 {
+            return (0.0, 1.0);
+        }
+
+        const Z: f64 = 1.96;
+        let n = self.games as f64;
+        let p = self.win_rate(player);
+        let center = (p + Z * Z / (2.0 * n)) / (1.0 + Z * Z / n);
+        let margin = Z / (1.0 + Z * Z / n) * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt();
+
+        ((center - margin).max(0.0), (center + margin).min(1.0))
+    }
+}
+
+/// Remaining thinking time for each player
+#[derive(Debug, Clone)]
+pub struct Clock {
+    remaining: Vec<Duration>,
+}
+
+impl Clock {
+    pub fn new(time_per_player: Duration, player_count: usize) -> Clock {
+        Clock {
+            remaining: vec![time_per_player; player_count],
+        }
+    }
+
+    pub fn remaining(&self, player: Player) -> Duration {
+        self.remaining[player as usize]
+    }
+
+    /// Takes `elapsed` off `player`'s clock, returns false if they ran out of time
+    pub fn charge(&mut self, player: Player, elapsed: Duration) -> bool {
+        let remaining = &mut self.remaining[player as usize];
+        *remaining = remaining.saturating_sub(elapsed);
+
+        !remaining.is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board_json(edit: impl FnOnce(&mut serde_json::Value)) -> String {
+        let mut value = serde_json::to_value(Board::new((3, 3))).unwrap();
+        edit(&mut value);
+        value.to_string()
+    }
+
+    #[test]
+    fn load_migrates_v1_saves() {
+        let v1 = r#"{
+            "start": { "current_turn": 0, "cells": [null, null, null, null], "size": [2, 2] },
+            "moves": [{ "Place": { "x": 0, "y": 0 } }]
+        }"#;
+
+        let game = Game::load(v1).unwrap();
+        assert_eq!(game.start.rules, Ruleset::default());
+        assert_eq!(game.moves, [Move::Place(c(0, 0))]);
+        assert_eq!(Game::load(&game.save()).unwrap().moves, game.moves);
+    }
+
+    #[test]
+    fn load_rejects_malformed_start_boards() {
+        let save = r#"{
+            "start": { "current_turn": 0, "cells": [], "size": [3, 3] },
+            "moves": [{ "Place": { "x": 0, "y": 0 } }]
+        }"#;
+
+        assert!(matches!(Game::load(save), Err(GameError::Corrupt(_))));
+
+        let mut start = serde_json::to_value(Board::from_compact("2x2/t0/XX/X.").unwrap()).unwrap();
+        start["rules"]["player_count"] = serde_json::json!(1);
+        let save = serde_json::json!({
+            "version": SAVE_FORMAT_VERSION,
+            "start": start,
+            "moves": [{ "Place": { "x": 1, "y": 1 } }],
+        });
+        assert!(matches!(
+            Game::load(&save.to_string()),
+            Err(GameError::Corrupt(_))
+        ));
+    }
+
+    /// `count` positions reached by seeded random play from `start`
+    fn random_positions(count: usize, start: &Board) -> Vec<Board> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..count)
+            .map(|_| {
+                let mut board = start.clone();
+                for _ in 0..rng.gen_range(0..start.total_cells() * 2) {
+                    match board.apply_random_move(&mut rng) {
+                        Some(new_state) => board = new_state,
+                        None => break,
+                    }
+                }
+                board
+            })
+            .collect()
+    }
+
+    /// `Board::from_compact` with a different `Ruleset::victory_threshold`
+    fn with_threshold(compact: &str, victory_threshold: usize) -> Board {
+        let mut board = Board::from_compact(compact).unwrap();
+        board.rules.victory_threshold = victory_threshold;
+        board
+    }
+
+    fn token(player: Player) -> Cell {
+        Some(Token {
+            player,
+            locked: false,
+        })
+    }
+
+    #[test]
+    fn stuck_players_are_skipped() {
+        // player 1's only token is boxed in by players 0 and 2
+        let board = Board::builder((3, 3))
+            .player_count(3)
+            .placement(PlacementRule::AdjacentToOwn)
+            .allow_swaps(false)
+            .build()
+            .unwrap()
+            .with_cell(c(0, 0), token(1))
+            .with_cell(c(1, 0), token(0))
+            .with_cell(c(0, 1), token(2));
+        assert_eq!(board.available_players_to_move(), [0, 2]);
+
+        let board = board.advance(Move::Place(c(2, 0))).unwrap();
+        assert_eq!(board.current_turn(), 2);
+        let board = board.advance(Move::Place(c(0, 2))).unwrap();
+        assert_eq!(board.current_turn(), 0);
+    }
+
+    #[test]
+    fn skipping_players_with_forced_swaps_stays_cheap() {
+        let mut board = Board::builder((4, 4))
+            .placement(PlacementRule::AdjacentToOwn)
+            .forced_scoring_swaps(true)
+            .build()
+            .unwrap();
+        for index in 0..10 {
+            board = board.with_cell(c(index % 4, index / 4), token(index as Player % 2));
+        }
+
+        let started = Instant::now();
+        let moves = board.get_legal_moves();
+        assert!(!moves.is_empty());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn reaching_the_target_wins_with_empty_cells_left() {
+        let mut board = Board::builder((4, 4))
+            .victory_threshold(0)
+            .target_points(2)
+            .build()
+            .unwrap();
+        for move_ in [c(0, 0), c(3, 3), c(0, 1)] {
+            assert_eq!(board.check_win_condition(), WinState::NotOver);
+            board = board.advance(Move::Place(move_)).unwrap();
+        }
+
+        assert_eq!(board.scores(), [2, 0]);
+        assert_eq!(board.check_win_condition(), WinState::Winner(0));
+        assert!(!board.get_legal_moves().is_empty());
+        let mut solver = Solver::with_depth_limit(2);
+        let (score, _) = solver.search(&board, 1, Some(2));
+        assert!(is_decisive(score) && score < 0);
+        assert_eq!(solver.best_moves(&board, 1).unwrap(), (score, Vec::new()));
+        assert_eq!(
+            solver.find_best_move_parallel(&board, 1, 4).unwrap(),
+            (score, None)
+        );
+    }
+
+    #[test]
+    fn playouts_stop_at_the_target() {
+        let board = Board::builder((3, 3))
+            .victory_threshold(0)
+            .target_points(1)
+            .build()
+            .unwrap()
+            .with_cell(c(0, 0), token(0))
+            .with_cell(c(1, 0), token(0));
+        assert_eq!(board.check_win_condition(), WinState::Winner(0));
+
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(board.apply_random_move(&mut rng), None);
+        assert_eq!(board.estimate_win_probability(0, 20, &mut rng), 1.0);
+    }
+
+    #[test]
+    fn tablebase_matches_a_full_search() {
+        for size in [(2, 2), (3, 2)] {
+            let tablebase = Tablebase::build(size);
+            let start = Board::new(size);
+            let mut solver = Solver::default();
+            assert_eq!(
+                tablebase.score(&start),
+                Some(solver.search(&start, 0, None).0)
+            );
+            // nothing can score on boards without a cell with 4 neighbors
+            assert_eq!(tablebase.probe(&start), WinState::Draw);
+
+            let reachable = start.reachable_positions(start.total_cells() * 4);
+            assert_eq!(tablebase.len(), reachable.len() + 1);
+            for board in &reachable {
+                assert!(tablebase.score(board).is_some(), "{}", board.compact());
+            }
+
+            // the table answers without searching, whatever the depth
+            let mut tablebase_solver = Solver::with_tablebase(tablebase.clone());
+            assert_eq!(
+                tablebase_solver.search(&start, 0, Some(1)).0,
+                tablebase.score(&start).unwrap()
+            );
+            assert_eq!(tablebase_solver.stats().nodes, 1);
+
+            for (board, score) in &tablebase.scores {
+                let player = board.current_turn;
+                assert_eq!(solver.search(board, player, None).0, *score);
+
+                let (tablebase_score, best_move) =
+                    Solver::with_tablebase(tablebase.clone()).search(board, player, None);
+                assert_eq!(tablebase_score, *score);
+                if let Some(best_move) = best_move {
+                    // the quickest win or slowest loss, not just any move that keeps the result
+                    let new_state = board.advance(best_move).unwrap();
+                    assert_eq!(add_ply(solver.search(&new_state, player, None).0), *score);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neutral_tokens_neighbor_nobody() {
+        let board = Board::from_compact("3x3/t0/.#./#.x/.o.").unwrap();
+
+        assert_eq!(board.neighbors_uniform_player(c(0, 0)), None);
+        assert_eq!(board.neighbors_uniform_player(c(2, 0)), Some(0));
+        assert_eq!(board.neighbors_uniform_player(c(1, 1)), None);
+        assert_eq!(board.neighbor_player_counts(c(1, 1)), [1, 1]);
+        assert_eq!(
+            board.neighbor_player_histogram(c(1, 1)),
+            HashMap::from([(0, 1), (1, 1)])
+        );
+        assert!(board.neighbor_player_histogram(c(0, 0)).is_empty());
+    }
+
+    #[test]
+    fn find_best_move_is_deterministic() {
+        for board in random_positions(100, &Board::new((4, 4))) {
+            let player = board.current_turn;
+            let mut solver = Solver::with_depth_limit(2);
+            let first = solver.find_best_move_scored(&board, player).unwrap();
+
+            assert_eq!(solver.find_best_move_scored(&board, player).unwrap(), first);
+            assert_eq!(solver.find_best_move(&board, player).unwrap(), first.1);
+            assert_eq!(
+                Solver::with_depth_limit(2)
+                    .find_best_move_scored(&board, player)
+                    .unwrap(),
+                first
+            );
+        }
+    }
+
+    #[test]
+    fn grade_is_antisymmetric() {
+        let solver = Solver::default();
+        for size in [(3, 3), (4, 4), (5, 5)] {
+            for victory_threshold in [1, 3] {
+                let start = Board::builder(size)
+                    .victory_threshold(victory_threshold)
+                    .build()
+                    .unwrap();
+                for board in random_positions(100, &start) {
+                    assert!(solver.grade_is_antisymmetric(&board), "{board}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn from_json_round_trips() {
+        let board = Board::from_compact("3x3/t1/x../.O./..#").unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+
+        assert_eq!(Board::from_json(&json).unwrap(), board);
+    }
+
+    #[test]
+    fn from_json_rejects_inconsistent_boards() {
+        let corrupt = [
+            board_json(|board| board["cells"] = serde_json::json!([])),
+            board_json(|board| board["playable"] = serde_json::json!([true, false])),
+            board_json(|board| board["current_turn"] = serde_json::json!(2)),
+            board_json(|board| board["turn_schedule"] = serde_json::json!([1, 0])),
+            board_json(|board| {
+                board["cells"][4] = serde_json::json!({ "player": 5, "locked": false })
+            }),
+            board_json(|board| board["size"] = serde_json::json!([(1u64 << 63) + 1, 2])),
+            board_json(|board| board["rules"]["radius"] = serde_json::json!(1u64 << 62)),
+            board_json(|board| board["rules"]["player_count"] = serde_json::json!(u32::MAX)),
+            board_json(|board| board["rules"]["victory_threshold"] = serde_json::json!(4)),
+        ];
+
+        for json in corrupt {
+            assert!(
+                matches!(Board::from_json(&json), Err(GameError::Corrupt(_))),
+                "{json}"
+            );
+        }
+
+        // would index past the scores when checking the winner
+        let mut one_player =
+            serde_json::to_value(Board::from_compact("2x2/t0/XX/XX").unwrap()).unwrap();
+        one_player["rules"]["player_count"] = serde_json::json!(1);
+        assert!(matches!(
+            Board::from_json(&one_player.to_string()),
+            Err(GameError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn progress_runs_from_empty_to_fully_scored() {
+        let empty = Board::new((3, 3));
+        assert_eq!(empty.progress(0), 0.0);
+        assert_eq!(empty.progress(1), 0.0);
+
+        let scored = with_threshold("3x3/t1/XXX/XXX/XXX", 1);
+        assert_eq!(scored.progress(0), 1.0);
+        assert_eq!(scored.progress(1), 0.0);
+    }
+
+    #[test]
+    fn move_constructors_match_manual_moves() {
+        assert_eq!(Move::place(1, 2), Move::Place(c(1, 2)));
+        assert_eq!(Move::swap((0, 1), (2, 3)), Move::Swap(c(0, 1), c(2, 3)));
+
+        let board = Board::new((3, 3));
+        assert_eq!(
+            board.try_place(1, 2).unwrap(),
+            board.advance(Move::Place(c(1, 2))).unwrap()
+        );
+        assert_eq!(board.try_place(3, 0), board.advance(Move::Place(c(3, 0))));
+    }
+
+    #[test]
+    fn occupancy_checks_agree_with_get_cell() {
+        let board = Board::from_compact("3x3/t0/xO./.#X/o..").unwrap();
+        for coordinate in board.coordinates() {
+            assert_eq!(
+                board.is_occupied(coordinate),
+                board.get_cell(coordinate).is_some()
+            );
+            assert_eq!(
+                board.is_empty_cell(coordinate),
+                board.get_cell(coordinate).is_none()
+            );
+        }
+    }
+
+    #[test]
+    fn random_moves_are_seeded() {
+        let board = Board::new((4, 4));
+        let play = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut board = board.clone();
+            for _ in 0..6 {
+                board = board.apply_random_move(&mut rng).unwrap();
+            }
+            board
+        };
+        assert_eq!(play(3), play(3));
+
+        let finished = Board::from_compact("2x2/t0/XO/OX").unwrap();
+        assert_ne!(finished.check_win_condition(), WinState::NotOver);
+        assert_eq!(
+            finished.apply_random_move(&mut StdRng::seed_from_u64(3)),
+            None
+        );
+    }
+
+    #[test]
+    fn move_cache_skips_move_generation() {
+        let board = with_threshold("3x3/t0/x../.o./...", 1);
+        let mut plain = Solver::with_depth_limit(4);
+        let mut cached = Solver {
+            depth_limit: Some(4),
+            ..Solver::with_move_cache(10_000)
+        };
+
+        assert_eq!(
+            cached.find_best_move_scored(&board, 0).unwrap(),
+            plain.find_best_move_scored(&board, 0).unwrap()
+        );
+        assert!(cached.stats().move_generations < plain.stats().move_generations);
+    }
+
+    /// Counts `trace` events whose message is `message`
+    #[cfg(feature = "trace")]
+    struct MessageCounter {
+        message: &'static str,
+        count: Arc<AtomicUsize>,
+    }
+
+    #[cfg(feature = "trace")]
+    impl tracing::Subscriber for MessageCounter {
+        fn enabled(&self, _: &tracing::Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event) {
+            struct Matches(&'static str, bool);
+
+            impl tracing::field::Visit for Matches {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    if field.name() == "message" && format!("{value:?}") == self.0 {
+                        self.1 = true;
+                    }
+                }
+            }
+
+            let mut matches = Matches(self.message, false);
+            event.record(&mut matches);
+            if matches.1 {
+                self.count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn search_traces_cutoffs() {
+        let board = with_threshold("3x3/t1/x.x/.../o.o", 1);
+        let count = Arc::new(AtomicUsize::new(0));
+        let subscriber = MessageCounter {
+            message: "cutoff",
+            count: Arc::clone(&count),
+        };
+
+        tracing::subscriber::with_default(subscriber, || {
+            Solver::with_depth_limit(3)
+                .find_best_move(&board, 1)
+                .unwrap()
+        });
+        assert!(count.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn swapped_players_mirror_victory_points() {
+        let board = with_threshold("3x3/t0/xx./xoo/.oo", 1);
+        let mut points = board.count_victory_points();
+        assert_ne!(points[0], points[1]);
+        points.reverse();
+
+        let swapped = board.swap_players();
+        assert_eq!(swapped.count_victory_points(), points);
+        assert_eq!(swapped.current_turn(), 1);
+    }
+
+    #[test]
+    fn hotspots_cover_offense_and_defense() {
+        let board = with_threshold("3x3/t0/x../.../..o", 0);
+
+        assert_eq!(board.potential_victory_cells(0), [c(1, 0), c(0, 1)]);
+        assert_eq!(board.potential_victory_cells(1), [c(2, 1), c(1, 2)]);
+        assert_eq!(board.hotspots(0), [c(1, 0), c(0, 1), c(2, 1), c(1, 2)]);
+    }
+
+    #[test]
+    fn neighbor_radius() {
+        let neighbors = |distance, radius| {
+            let board = Board::builder((5, 5))
+                .distance(distance)
+                .radius(radius)
+                .build()
+                .unwrap();
+            let mut neighbors = board.cells_neighbor_coordinates(c(2, 2));
+            neighbors.sort_by_key(|c| (c.y, c.x));
+            neighbors
+        };
+
+        assert_eq!(
+            neighbors(Distance::Manhattan, 1),
+            [c(2, 1), c(1, 2), c(3, 2), c(2, 3)]
+        );
+        let ring = neighbors(Distance::Manhattan, 2);
+        assert_eq!(ring.len(), 12);
+        assert!(ring.contains(&c(2, 0)) && ring.contains(&c(1, 1)) && !ring.contains(&c(0, 0)));
+        assert_eq!(neighbors(Distance::Chebyshev, 1).len(), 8);
+        assert_eq!(neighbors(Distance::Chebyshev, 2).len(), 24);
+        // clipped by the edge
+        let corner = Board::builder((5, 5)).radius(2).build().unwrap();
+        assert_eq!(corner.cells_neighbor_coordinates(c(0, 0)).len(), 5);
+    }
+
+    struct Overthinker(RandomController);
+
+    impl Controller for Overthinker {
+        fn choose_action(&mut self, board: &Board) -> ControllerAction {
+            std::thread::sleep(Duration::from_millis(20));
+            self.0.choose_action(board)
+        }
+    }
+
+    #[test]
+    fn running_out_of_time_loses() {
+        let mut game = Game::new(Board::new((4, 4)));
+        let mut fast = RandomController::new(1);
+        let mut slow = Overthinker(RandomController::new(2));
+        let mut clock = Clock::new(Duration::from_millis(30), 2);
+
+        let end = game.play_match(&mut [&mut fast, &mut slow], Some(&mut clock));
+        assert_eq!(end, GameEnd::Timeout { loser: 1 });
+        assert_eq!(end.winner(), Some(0));
+        assert_eq!(clock.remaining(1), Duration::ZERO);
+    }
+
+    #[test]
+    fn swapped_colors_mirror_victory_points() {
+        let board = with_threshold("3x3/t0/xx./xoO/.oo", 1);
+        let mut points = board.count_victory_points();
+        points.reverse();
+
+        let swapped = board.swap_colors();
+        assert_eq!(swapped.count_victory_points(), points);
+        assert_eq!(swapped.owner(c(0, 0)), Some(1));
+        assert!(swapped.is_locked(c(2, 1)));
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let board = Board::from_compact("3x3/t1/xO./.#X/o.o").unwrap();
+        let bytes = board.to_bytes();
+        assert_eq!(Board::from_bytes(&bytes).unwrap(), board);
+
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                Board::from_bytes(&bytes[..len]),
+                Err(GameError::Truncated)
+            ));
+        }
+        let mut long = bytes.clone();
+        long.push(0);
+        assert!(matches!(
+            Board::from_bytes(&long),
+            Err(GameError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn preview_reports_newly_locked_cells() {
+        let board = with_threshold("3x3/t0/x../.../..o", 0);
+        let preview = board.preview(Move::Place(c(1, 0))).unwrap();
+
+        assert_eq!(preview.moved, [c(1, 0)]);
+        assert_eq!(preview.newly_locked, [c(0, 0), c(1, 0)]);
+        assert_eq!(
+            board.preview(Move::Place(c(2, 2))),
+            Err(MoveError::Occupied(c(2, 2)))
+        );
+    }
+
+    #[test]
+    fn forced_draws() {
+        let mut solver = Solver::default();
+        // nothing can ever score on 2x2
+        assert!(solver.is_forced_draw(&Board::from_compact("2x2/t0/xo/o.").unwrap(), 4));
+
+        let won = with_threshold("3x1/t0/x.x", 1);
+        assert!(!solver.is_forced_draw(&won, 4));
+    }
+
+    #[test]
+    fn neighbor_player_counts_per_player() {
+        let board = Board::from_compact("3x3/t0/.x./oxo/.#.").unwrap();
+
+        assert_eq!(board.neighbor_player_counts(c(1, 1)), [1, 2]);
+        assert_eq!(board.neighbor_player_counts(c(0, 0)), [1, 1]);
+    }
+
+    #[test]
+    fn full_solve_of_large_boards_is_refused() {
+        let board = Board::new((8, 8));
+
+        assert_eq!(
+            Solver::default().find_best_move(&board, 0),
+            Err(SolverError::TooLarge { cells: 64 })
+        );
+        assert!(Solver::with_depth_limit(1)
+            .find_best_move(&board, 0)
+            .is_ok());
+    }
+
+    #[test]
+    fn neighbor_histogram_per_player() {
+        let board = Board::from_compact("3x3/t0/.x./oxo/.#.").unwrap();
+
+        assert_eq!(
+            board.neighbor_player_histogram(c(1, 1)),
+            HashMap::from([(0, 1), (1, 2)])
+        );
+        assert_eq!(
+            board.neighbor_player_histogram(c(0, 0)),
+            HashMap::from([(0, 1), (1, 1)])
+        );
+        assert!(Board::new((3, 3))
+            .neighbor_player_histogram(c(1, 1))
+            .is_empty());
+    }
+
+    #[test]
+    fn holes_are_not_played_on_or_neighbored() {
+        let plus = vec![false, true, false, true, true, true, false, true, false];
+        let board = Board::with_playable_mask((3, 3), plus);
+        let corners = [c(0, 0), c(2, 0), c(0, 2), c(2, 2)];
+
+        for move_ in board.get_legal_moves() {
+            let Move::Place(target) = move_ else {
+                panic!("empty board has swaps")
+            };
+            assert!(!corners.contains(&target));
+        }
+        assert_eq!(board.get_legal_moves().len(), 5);
+        assert_eq!(board.cells_neighbor_coordinates(c(1, 0)), [c(1, 1)]);
+        assert_eq!(
+            board.advance(Move::Place(c(0, 0))),
+            Err(MoveError::NotPlayable(c(0, 0)))
+        );
+        assert_eq!(board.to_string().lines().nth(1), Some(" . "));
+    }
+
+    #[test]
+    fn forced_scoring_swaps_exclude_other_moves() {
+        let forced = |compact| {
+            let mut board = with_threshold(compact, 0);
+            board.rules.forced_scoring_swaps = true;
+            board
+        };
+
+        // swapping the `o` with either `x` brings two `x` together
+        let board = forced("4x1/t0/xox.");
+        let moves = board.get_legal_moves();
+        assert!(!moves.is_empty());
+        for move_ in moves {
+            assert!(matches!(move_, Move::Swap(..)), "{move_:?}");
+            assert!(board.advance(move_).unwrap().count_victory_points()[0] > 0);
+        }
+
+        let board = forced("4x1/t0/x..o");
+        let mut unforced = board.clone();
+        unforced.rules.forced_scoring_swaps = false;
+        assert_eq!(board.get_legal_moves(), unforced.get_legal_moves());
+    }
+
+    #[test]
+    fn advance_checked_rejects_self_swaps() {
+        let board = Board::from_compact("2x2/t0/xo/..").unwrap();
+        let self_swap = Move::Swap(c(0, 0), c(0, 0));
+
+        assert!(!board.is_legal(self_swap));
+        assert!(matches!(
+            board.advance_checked(self_swap),
+            Err(GameError::IllegalMove(move_)) if move_ == self_swap
+        ));
+        assert!(board.advance_checked(Move::Place(c(0, 1))).is_ok());
+    }
+
+    #[test]
+    fn win_probability_of_decided_positions() {
+        let board = with_threshold("3x3/t1/XXX/XXX/x..", 1);
+        let mut rng = StdRng::seed_from_u64(5);
+
+        assert!(board.estimate_win_probability(0, 50, &mut rng) > 0.9);
+        assert!(board.estimate_win_probability(1, 50, &mut rng) < 0.1);
+    }
+
+    #[test]
+    fn grid_matches_get_cell() {
+        let board = Board::from_compact("3x2/t0/xO./.#X").unwrap();
+        let grid = board.as_grid();
+
+        assert_eq!(grid.len(), 2);
+        for coordinate in board.coordinates() {
+            assert_eq!(grid[coordinate.y][coordinate.x], board.get_cell(coordinate));
+        }
+    }
+
+    #[test]
+    fn debug_shows_the_grid() {
+        let board = Board::from_compact("3x2/t1/xO./.#X").unwrap();
+        let debug = format!("{board:?}");
+
+        assert!(debug.contains("size: (3, 2)"), "{debug}");
+        assert!(debug.contains("current_turn: 1"), "{debug}");
+        assert!(debug.contains("xO.") && debug.contains(".#X"), "{debug}");
+        assert!(!board.to_string().contains("rules"));
+    }
+
+    #[test]
+    fn moves_from_each_kind_of_cell() {
+        let board = Board::from_compact("3x1/t0/x.O").unwrap();
+
+        assert_eq!(board.moves_from(c(1, 0)), [Move::Place(c(1, 0))]);
+        assert!(board.moves_from(c(2, 0)).is_empty());
+        // the only other token is locked
+        assert!(board.moves_from(c(0, 0)).is_empty());
+
+        let board = Board::from_compact("3x1/t0/x.o").unwrap();
+        let swaps = board.moves_from(c(0, 0));
+        assert!(!swaps.is_empty());
+        for move_ in swaps {
+            let Move::Swap(a, b) = move_ else {
+                panic!("{move_:?} is not a swap")
+            };
+            assert!([a, b].contains(&c(0, 0)) && [a, b].contains(&c(2, 0)));
+        }
+    }
+
+    #[test]
+    fn largest_group_scoring() {
+        let mut board = Board::from_compact("4x3/t0/xxxx/o.o./.o.o").unwrap();
+        board.rules.scoring = Scoring::LargestGroup;
+
+        assert_eq!(board.largest_groups(), [4, 1]);
+        assert_eq!(board.scores(), [4, 1]);
+        assert_eq!(board.result_by_points(), WinState::Winner(0));
+    }
+
+    #[test]
+    fn builder_configures_the_rules() {
+        let board = Board::builder((5, 4))
+            .wrap(true)
+            .distance(Distance::Chebyshev)
+            .player_count(3)
+            .victory_threshold(5)
+            .build()
+            .unwrap();
+
+        assert_eq!(board.size, (5, 4));
+        assert!(board.rules.wrap);
+        assert_eq!(board.rules.distance, Distance::Chebyshev);
+        assert_eq!(board.rules.player_count, 3);
+        assert_eq!(board.rules.victory_threshold, 5);
+        // wrapping gives every cell all 8 neighbors
+        assert_eq!(board.cells_neighbor_coordinates(c(0, 0)).len(), 8);
+
+        assert!(matches!(
+            Board::builder((5, 4)).victory_threshold(4).build(),
+            Err(GameError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            Board::builder((5, 4)).player_count(1).build(),
+            Err(GameError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn principal_variation_ends_in_the_forced_win() {
+        let board = with_threshold("3x3/t1/o.X/X.o/OXO", 1);
+        let line = Solver::default().principal_variation(&board, 1).unwrap();
+        assert_eq!(line.len(), 3);
+
+        let end = line
+            .iter()
+            .fold(board, |board, move_| board.advance(*move_).unwrap());
+        assert_eq!(end.check_win_condition(), WinState::Winner(1));
+    }
+
+    #[test]
+    fn coordinates_are_row_major() {
+        let board = Board::new((3, 2));
+        let coordinates: Vec<Coordinate> = board.coordinates().collect();
+
+        assert_eq!(
+            coordinates,
+            [c(0, 0), c(1, 0), c(2, 0), c(0, 1), c(1, 1), c(2, 1)]
+        );
+    }
+
+    #[test]
+    fn last_move_is_remembered() {
+        let board = Board::new((3, 3));
+        assert_eq!(board.last_move(), None);
+        assert_eq!(board.with_cell(c(0, 0), token(0)).last_move(), None);
+
+        let board = board.advance(Move::Place(c(1, 2))).unwrap();
+        assert_eq!(board.last_move(), Some(Move::Place(c(1, 2))));
+        // the position is the same however it was reached
+        let mut placed = Board::new((3, 3)).with_cell(c(1, 2), token(0));
+        placed.current_turn = 1;
+        assert_eq!(board, placed);
+    }
+
+    #[test]
+    fn resigning_awards_the_opponent() {
+        let mut game = Game::new(with_threshold("3x3/t0/XXX/X../...", 1));
+        game.resign(0);
+
+        assert_eq!(game.outcome(), GameEnd::Resignation { loser: 0 });
+        assert_eq!(game.outcome().winner(), Some(1));
+        // the first resignation stands
+        game.resign(1);
+        assert_eq!(game.outcome().winner(), Some(1));
+    }
+
+    #[test]
+    fn eval_cache_skips_grading() {
+        let board = with_threshold("4x4/t0/x.../.o../..x./...o", 1);
+        let mut plain = Solver::with_depth_limit(4);
+        let mut cached = Solver {
+            depth_limit: Some(4),
+            ..Solver::with_eval_cache(10_000)
+        };
+
+        assert_eq!(
+            cached.find_best_move_scored(&board, 0).unwrap(),
+            plain.find_best_move_scored(&board, 0).unwrap()
+        );
+        assert!(cached.stats().grade_calls < plain.stats().grade_calls);
+    }
+
+    #[test]
+    fn eval_cache_tells_games_apart() {
+        let strict = Board::from_compact("3x2/t0/xx./o..").unwrap();
+        let loose = with_threshold("3x2/t0/xx./o..", 0);
+        let narrow = with_threshold("2x3/t0/xx/.o/..", 0);
+        let mut masked =
+            Board::with_playable_mask((3, 2), vec![true, true, false, true, true, true]);
+        masked.rules.victory_threshold = 0;
+        let masked = masked
+            .with_cell(c(0, 0), token(0))
+            .with_cell(c(1, 0), token(0))
+            .with_cell(c(0, 1), token(1));
+        assert_eq!(strict.hash_key(), loose.hash_key());
+        assert_eq!(loose.hash_key(), narrow.hash_key());
+
+        let mut solver = Solver::with_eval_cache(100);
+        for board in [&strict, &loose, &narrow, &masked, &strict] {
+            assert_eq!(solver.evaluate(board, 0), solver.grade(board));
+        }
+        assert_ne!(solver.grade(&strict), solver.grade(&loose));
+        assert_ne!(solver.grade(&loose), solver.grade(&narrow));
+        assert_ne!(solver.grade(&loose), solver.grade(&masked));
+    }
+
+    #[test]
+    fn puzzle_uniqueness() {
+        let unique = with_threshold("3x3/t1/o.X/X.o/OXO", 1);
+        let report = unique
+            .validate_puzzle(
+                &[
+                    Move::Place(c(1, 1)),
+                    Move::Swap(c(0, 0), c(1, 1)),
+                    Move::Place(c(1, 0)),
+                ],
+                1,
+            )
+            .unwrap();
+        assert_eq!(
+            report,
+            PuzzleReport {
+                wins: true,
+                unique: true
+            }
+        );
+
+        let ambiguous = with_threshold("3x3/t0/OX./OXO/OX.", 1);
+        let report = ambiguous
+            .validate_puzzle(
+                &[
+                    Move::Place(c(2, 0)),
+                    Move::Place(c(2, 2)),
+                    Move::Swap(c(2, 0), c(2, 2)),
+                ],
+                0,
+            )
+            .unwrap();
+        assert_eq!(
+            report,
+            PuzzleReport {
+                wins: true,
+                unique: false
+            }
+        );
+
+        let illegal = Move::Place(c(0, 0));
+        assert!(matches!(
+            unique.validate_puzzle(&[illegal], 1),
+            Err(GameError::IllegalMove(move_)) if move_ == illegal
+        ));
+    }
+
+    #[test]
+    fn clone_into_matches_clone() {
+        let board = Board::from_compact("3x3/t1/xO./.#X/o.o").unwrap();
+        let mut reused = Board::new((5, 5));
+        board.clone_into(&mut reused);
+
+        assert_eq!(reused, board.clone());
+        assert_eq!(reused.size, board.size);
+        assert_eq!(reused.last_move(), board.last_move());
+    }
+
+    #[test]
+    fn players_present_is_sorted() {
+        let board = Board::builder((3, 1))
+            .player_count(3)
+            .build()
+            .unwrap()
+            .with_cell(c(0, 0), token(2))
+            .with_cell(c(2, 0), token(0))
+            .place_neutral(c(1, 0));
+
+        assert_eq!(board.players_present(), [0, 2]);
+        assert!(Board::new((2, 2)).players_present().is_empty());
+    }
+
+    #[test]
+    fn bytes_are_smaller_than_json() {
+        let start = Board::new((6, 6));
+        for board in random_positions(50, &start) {
+            let bytes = board.to_bytes();
+            assert_eq!(Board::from_bytes(&bytes).unwrap(), board);
+            assert!(bytes.len() * 10 < serde_json::to_string(&board).unwrap().len());
+        }
+    }
+
+    #[test]
+    fn tie_breaks() {
+        // two points each, player 0 has more locked tokens and player 1 fewer tokens
+        let tied = |tie_break| {
+            let mut board = with_threshold("4x3/t0/XX.x/..../oo..", 0);
+            board.rules.tie_break = tie_break;
+            board
+        };
+        assert_eq!(tied(TieBreak::DrawOnTie).scores(), [2, 2]);
+
+        assert_eq!(tied(TieBreak::DrawOnTie).result_by_points(), WinState::Draw);
+        assert_eq!(
+            tied(TieBreak::MostLocked).result_by_points(),
+            WinState::Winner(0)
+        );
+        assert_eq!(
+            tied(TieBreak::FirstToReach).result_by_points(),
+            WinState::Winner(1)
+        );
+    }
+
+    #[test]
+    fn threat_denial_blocks_the_opponent() {
+        // only `o` taking the center stops `x` from winning
+        let board = with_threshold("3x3/t1/oxx/X../o.O", 1);
+        assert!(board.potential_victory_cells(0).contains(&c(1, 1)));
+        assert_eq!(board.opponent_threats(1), board.threat_count(0));
+
+        assert_eq!(
+            Solver::with_threat_denial(10).heuristic_move(&board),
+            Some(Move::Place(c(1, 1)))
+        );
+        assert_ne!(
+            Solver::default().heuristic_move(&board),
+            Some(Move::Place(c(1, 1)))
+        );
+    }
+
+    #[test]
+    fn render_cells_matches_the_board() {
+        let board = Board::from_compact("3x2/t0/xO./.#X").unwrap();
+        let cells = board.render_cells();
+
+        assert_eq!(cells.len(), board.total_cells());
+        assert_eq!(
+            cells[1],
+            CellView {
+                coordinate: c(1, 0),
+                player: Some(1),
+                locked: true
+            }
+        );
+        assert_eq!(cells[3].player, None);
+        assert_eq!(cells[4].player, Some(NEUTRAL));
+        assert_eq!(cells.iter().filter(|cell| cell.locked).count(), 2);
+    }
+
+    #[test]
+    fn columns_transpose_rows() {
+        let board = Board::from_compact("3x3/t0/xO./.#X/o..").unwrap();
+        let rows: Vec<Vec<Cell>> = board.rows().collect();
+        let columns: Vec<Vec<Cell>> = board.columns().collect();
+
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|row| row.len() == 3));
+        for (x, column) in columns.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                assert_eq!(*cell, rows[y][x]);
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_art_with_a_legend() {
+        let legend = HashMap::from([('#', 0), ('@', 1)]);
+        let board = Board::from_ascii_art(
+            "
+            #.@
+            @#.
+            ",
+            &legend,
+        )
+        .unwrap();
+
+        assert_eq!(board.size, (3, 2));
+        assert_eq!(board.owner(c(0, 0)), Some(0));
+        assert_eq!(board.owner(c(2, 0)), Some(1));
+        assert_eq!(board.owner(c(0, 1)), Some(1));
+        assert_eq!(board.owner(c(1, 1)), Some(0));
+        assert_eq!(board.owner(c(1, 0)), None);
+        assert!(matches!(
+            Board::from_ascii_art("#x", &legend),
+            Err(GameError::UnknownGlyph('x'))
+        ));
+    }
+
+    struct Resigner;
+
+    impl Controller for Resigner {
+        fn choose_action(&mut self, _: &Board) -> ControllerAction {
+            ControllerAction::Resign
+        }
+    }
+
+    #[test]
+    fn resigning_controller_ends_the_game() {
+        let mut game = Game::new(Board::new((3, 3)));
+        let mut random = RandomController::new(1);
+
+        let end = game.play_match(&mut [&mut random, &mut Resigner], None);
+        assert_eq!(end, GameEnd::Resignation { loser: 1 });
+        assert_eq!(game.moves().len(), 1);
+    }
+
+    #[test]
+    fn advance_reports_newly_locked_cells() {
+        let board = with_threshold("3x3/t0/x../.../..o", 0);
+        let mut new_state = board.clone();
+        let locked = board
+            .advance_with_events(Move::Place(c(0, 1)), &mut new_state)
+            .unwrap();
+
+        assert_eq!(locked, [c(0, 0), c(0, 1)]);
+        assert_eq!(new_state, board.advance(Move::Place(c(0, 1))).unwrap());
+        assert!(board
+            .advance_with_events(Move::Place(c(2, 0)), &mut new_state)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn search_avoids_refuted_moves() {
+        let board = with_threshold("3x3/t0/OOX/XOo/x.x", 1);
+        let value = |move_| {
+            let new_state = board.advance(move_).unwrap();
+            add_ply(Solver::default().search(&new_state, 0, None).0)
+        };
+
+        // the greedy swap hands `o` a forced win
+        let greedy = Solver::default().heuristic_move(&board).unwrap();
+        assert!(value(greedy) < 0);
+
+        let (score, best_move) = Solver::default().find_best_move_scored(&board, 0).unwrap();
+        assert_eq!(score, 0);
+        assert_eq!(value(best_move.unwrap()), score);
+    }
+
+    #[test]
+    fn owner_of_cells() {
+        let board = Board::from_compact("2x2/t0/xO/..").unwrap();
+
+        assert_eq!(board.owner(c(0, 0)), Some(0));
+        assert_eq!(board.owner(c(1, 0)), Some(1));
+        assert_eq!(board.owner(c(0, 1)), None);
+        assert_eq!(board.owner(c(2, 0)), None);
+        assert_eq!(board.owner(c(0, 5)), None);
+    }
+
+    #[test]
+    fn progress_callback_fires_per_depth() {
+        let depths = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let mut solver = Solver::default();
+        let seen = std::rc::Rc::clone(&depths);
+        solver.set_progress_callback(move |progress| seen.borrow_mut().push(progress.depth));
+
+        solver.find_best_move_iterative(&Board::new((3, 3)), 0, 3);
+        assert_eq!(*depths.borrow(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn region_is_the_cell_and_its_neighbors() {
+        let board = Board::from_compact("3x3/t0/.x./o.o/...").unwrap();
+        let region = board.region(c(1, 1));
+
+        assert_eq!(region.len(), 5);
+        assert_eq!(region[0], (None, c(1, 1)));
+        assert_eq!(region.iter().filter(|(cell, _)| cell.is_some()).count(), 3);
+        assert_eq!(board.region(c(0, 0)).len(), 3);
+    }
+
+    #[test]
+    fn tournaments_are_reproducible() {
+        let run = |threads| {
+            let finished = AtomicUsize::new(0);
+            let tournament = Tournament::run(
+                &Board::new((3, 3)),
+                8,
+                threads,
+                42,
+                |rng| {
+                    vec![
+                        Box::new(RandomController::new(rng.gen())) as Box<dyn Controller>,
+                        Box::new(RandomController::new(rng.gen())),
+                    ]
+                },
+                |_, _| {
+                    finished.fetch_add(1, Ordering::Relaxed);
+                },
+            );
+            assert_eq!(finished.into_inner(), 8);
+            tournament
+        };
+
+        let single = run(1);
+        let threaded = run(4);
+        assert_eq!(single.wins, threaded.wins);
+        assert_eq!(single.draws, threaded.draws);
+        assert_eq!(single.wins.iter().sum::<usize>() + single.draws, 8);
+        let (low, high) = single.confidence_interval(0);
+        assert!(low <= single.win_rate(0) && single.win_rate(0) <= high);
+    }
+
+    #[test]
+    fn free_neighbors_of_corners_edges_and_center() {
+        let board = Board::from_compact("3x3/t0/.x./.../...").unwrap();
+
+        assert_eq!(board.free_neighbor_count(c(0, 0)), 1);
+        assert_eq!(board.free_neighbor_count(c(2, 2)), 2);
+        assert_eq!(board.free_neighbor_count(c(0, 1)), 3);
+        assert_eq!(board.free_neighbor_count(c(1, 1)), 3);
+    }
+
+    #[test]
+    fn selfplay_is_seeded() {
+        let generate = || generate_selfplay((3, 3), 2, 1, &mut StdRng::seed_from_u64(3));
+
+        let positions = generate();
+        assert!(!positions.is_empty());
+        assert_eq!(positions, generate());
+        assert_eq!(positions[0].0, Board::new((3, 3)));
+    }
+
+    #[test]
+    fn contested_cells_touch_both_players() {
+        let board = Board::from_compact("3x3/t0/x.o/.../#..").unwrap();
+        assert_eq!(board.contested_cells(), [c(1, 0)]);
+
+        let board = Board::from_compact("3x3/t0/x../.../..#").unwrap();
+        assert!(board.contested_cells().is_empty());
+    }
+
+    #[test]
+    fn json_moves_round_trip() {
+        let board = Board::from_compact("3x3/t0/x.o/.../...").unwrap();
+        let moves: Vec<serde_json::Value> =
+            serde_json::from_str(&board.legal_moves_json()).unwrap();
+        assert_eq!(moves.len(), board.get_legal_moves().len());
+
+        for (json, move_) in moves.iter().zip(board.get_legal_moves()) {
+            assert_eq!(
+                board.play_move_json(&json.to_string()).unwrap(),
+                board.advance(move_).unwrap()
+            );
+        }
+        assert!(board
+            .play_move_json(r#"{"type":"place","x":0,"y":0}"#)
+            .is_err());
+    }
+
+    #[test]
+    fn swap_score_delta_matches_the_swapped_board() {
+        let board = with_threshold("4x1/t0/xox.", 0);
+
+        assert_eq!(board.swap_score_delta(c(1, 0), c(2, 0), 0), 2);
+        assert_eq!(board.swap_score_delta(c(1, 0), c(2, 0), 1), 0);
+        assert_eq!(board.swap_score_delta(c(0, 0), c(2, 0), 0), 0);
+    }
+
+    #[test]
+    fn search_tree_agrees_with_the_search() {
+        let board = with_threshold("3x3/t0/x.o/.../.x.", 1);
+        let tree = Solver::default().search_tree(&board, 2);
+
+        assert_eq!(tree.children.len(), board.get_legal_moves().len());
+        let (score, _) = Solver::with_depth_limit(2)
+            .find_best_move_scored(&board, 0)
+            .unwrap();
+        assert_eq!(tree.score, score);
+    }
+
+    #[test]
+    fn with_cell_bypasses_the_rules() {
+        let board = with_threshold("3x1/t0/x..", 0);
+
+        let placed = board.with_cell(c(1, 0), token(0));
+        assert_eq!(placed.current_turn(), 0);
+        assert!(!placed.is_locked(c(0, 0)));
+        assert_eq!(placed.with_cell(c(1, 0), None), board);
+    }
+
+    #[test]
+    fn neutral_tokens_never_score() {
+        let board = with_threshold("3x1/t0/...", 0).place_neutral(c(1, 0));
+
+        assert!(board.is_occupied(c(1, 0)));
+        assert_eq!(
+            board.advance(Move::Place(c(1, 0))),
+            Err(MoveError::Occupied(c(1, 0)))
+        );
+
+        let board = board.with_cell(c(0, 0), token(0));
+        assert_eq!(board.count_victory_points(), [0, 0]);
+        assert_eq!(board.owner(c(1, 0)), Some(NEUTRAL));
+    }
+
+    #[test]
+    fn potential_victory_cells_raise_the_score() {
+        for board in random_positions(30, &with_threshold("3x3/t0/.../.../...", 1)) {
+            for player in 0..2 {
+                let points = board.count_victory_points()[player as usize];
+                for cell in board.potential_victory_cells(player) {
+                    let filled = board.with_cell(cell, token(player));
+                    assert!(filled.count_victory_points()[player as usize] > points);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn negamax_matches_explicit_minimax() {
+        let start = with_threshold("3x3/t0/.../.../...", 1);
+
+        for board in random_positions(20, &start) {
+            let tree = Solver::default().search_tree(&board, 3);
+            let (score, best_move) = Solver::with_depth_limit(3)
+                .find_best_move_scored(&board, board.current_turn())
+                .unwrap();
+            assert_eq!(score, tree.score, "{}", board.compact());
+
+            if let Some(best_move) = best_move {
+                let (_, child) = tree
+                    .children
+                    .iter()
+                    .find(|(move_, _)| *move_ == best_move)
+                    .unwrap();
+                assert_eq!(add_ply(child.score), tree.score, "{}", board.compact());
+            }
+        }
+    }
+
+    #[test]
+    fn compact_round_trips() {
+        let board = Board::from_compact("4x2/t1/xO#_/.X.o").unwrap();
+
+        assert_eq!(board.compact(), "4x2/t1/xO#_/.X.o");
+        assert!(!board.compact().contains(char::is_whitespace));
+        assert_eq!(Board::from_compact(&board.compact()).unwrap(), board);
+        assert!(Board::from_compact("2x2/t0/x.").is_err());
+    }
+
+    #[test]
+    fn mobility_counts_legal_moves() {
+        let mut forced = Board::new((3, 3));
+        forced.rules.forced_scoring_swaps = true;
+        let mut no_swaps = Board::new((3, 3));
+        no_swaps.rules.allow_swaps = false;
+
+        for start in [Board::new((3, 3)), forced, no_swaps] {
+            for board in random_positions(20, &start) {
+                assert_eq!(board.mobility(), board.get_legal_moves().len());
+            }
+        }
+    }
+
+    #[test]
+    fn best_moves_returns_every_tie() {
+        let board = Board::new((2, 2));
+        let (score, moves) = Solver::default().best_moves(&board, 0).unwrap();
+
+        assert_eq!(score, 0);
+        assert_eq!(moves.len(), 4);
+        assert!(moves.iter().all(|move_| matches!(move_, Move::Place(_))));
+    }
+
+    #[test]
+    fn distances_go_around_walls() {
+        let board = Board::from_compact("3x3/t0/.x./.x./...").unwrap();
+        let distances = board.distance_from(c(0, 0), false);
+
+        assert_eq!(distances[0][0], Some(0));
+        assert_eq!(distances[2][2], Some(4));
+        assert_eq!(distances[0][2], Some(6));
+        assert_eq!(distances[0][1], None);
+        assert_eq!(board.distance_from(c(0, 0), true)[0][2], Some(2));
+
+        let walled_in = Board::from_compact("3x3/t0/.x./x../...").unwrap();
+        let distances = walled_in.distance_from(c(0, 0), false);
+        assert_eq!(distances.iter().flatten().flatten().count(), 1);
+    }
+
+    #[test]
+    fn corner_start() {
+        let board = Board::from_start((4, 4), StartPosition::Corners, 1).unwrap();
+
+        assert_eq!(board.current_turn(), 1);
+        assert_eq!(board.owner(c(0, 0)), Some(0));
+        assert_eq!(board.owner(c(3, 3)), Some(0));
+        assert_eq!(board.owner(c(3, 0)), Some(1));
+        assert_eq!(board.owner(c(0, 3)), Some(1));
+        assert_eq!(board.locked_count(), 0);
+        assert!(Board::from_start((1, 1), StartPosition::Corners, 0).is_err());
+    }
+
+    #[test]
+    fn self_swaps_are_rejected() {
+        let board = Board::from_compact("3x1/t0/xo.").unwrap();
+        let swap = Move::Swap(c(0, 0), c(0, 0));
+
+        assert!(!board.get_legal_moves().contains(&swap));
+        assert_eq!(board.advance_strict(swap), Err(MoveError::Illegal(swap)));
+    }
+
+    #[test]
+    fn locked_full_boards_make_no_progress() {
+        let board = Board::from_compact("2x2/t0/XO/OX").unwrap();
+        assert!(board.no_progress_possible());
+        assert_eq!(board.check_win_condition(), WinState::Draw);
+
+        assert!(!with_threshold("2x2/t0/XO/O.", 1).no_progress_possible());
+
+        // over with a cell left to fill, so there is nothing to search
+        let settled = Board::from_compact("3x3/t0/XOX/OXO/XO.").unwrap();
+        assert!(settled.no_progress_possible());
+        assert_eq!(settled.check_win_condition(), WinState::Draw);
+        assert_eq!(Solver::default().find_best_move(&settled, 0), Ok(None));
+        assert_eq!(
+            Solver::default().best_moves(&settled, 0),
+            Ok((0, Vec::new()))
+        );
+        assert_eq!(
+            Solver::default().find_best_move_parallel(&settled, 0, 4),
+            Ok((0, None))
+        );
+        assert!(Solver::default()
+            .search_tree(&settled, 2)
+            .children
+            .is_empty());
+        assert!(Solver::default().is_forced_draw(&settled, 2));
+        assert!(!Board::from_compact("2x2/t0/XO/Ox")
+            .unwrap()
+            .no_progress_possible());
+    }
+
+    #[test]
+    fn every_move_is_timed() {
+        let mut game = Game::new(Board::new((3, 3)));
+        let (mut first, mut second) = (RandomController::new(1), RandomController::new(2));
+        game.play_match(&mut [&mut first, &mut second], None);
+
+        assert!(!game.moves().is_empty());
+        assert_eq!(game.move_times().len(), game.moves().len());
+
+        let mut game = Game::new(Board::new((3, 3)));
+        game.play(Move::Place(c(0, 0))).unwrap();
+        assert_eq!(game.move_times(), [Duration::ZERO]);
+    }
+
+    #[test]
+    fn locked_tokens_cannot_be_swapped() {
+        let board = Board::from_compact("3x1/t0/Xo.").unwrap();
+        let swap = Move::Swap(c(0, 0), c(1, 0));
+
+        assert!(!board.get_legal_moves().contains(&swap));
+        assert!(!board.is_legal(swap));
+        assert_eq!(board.advance(swap), Err(MoveError::Locked(c(0, 0))));
+        assert_eq!(board.advance_strict(swap), Err(MoveError::Locked(c(0, 0))));
+
+        let mut target = board.clone();
+        assert!(board.advance_into(swap, &mut target).is_err());
+        assert_eq!(target, board);
+    }
+
+    #[test]
+    fn reachable_positions_of_an_empty_board() {
+        let board = Board::new((2, 2));
+
+        assert_eq!(board.reachable_positions(1).len(), 4);
+        assert!(board.reachable_positions(2).len() > 4);
+        assert!(board.reachable_positions(0).is_empty());
+    }
+
+    #[test]
+    fn uniform_neighbors() {
+        let board = Board::from_compact("3x3/t0/.o./o.O/.#.").unwrap();
+        assert_eq!(board.neighbors_uniform_player(c(1, 1)), Some(1));
+
+        let board = board.with_cell(c(1, 2), token(0));
+        assert_eq!(board.neighbors_uniform_player(c(1, 1)), None);
+        assert_eq!(board.neighbors_uniform_player(c(0, 0)), Some(1));
+        assert_eq!(Board::new((3, 3)).neighbors_uniform_player(c(1, 1)), None);
+    }
+
+    #[test]
+    fn second_player_can_start() {
+        let board = Board::new_starting_with((3, 3), 1);
+        assert_eq!(board.current_turn(), 1);
+
+        let board = board.advance(Move::Place(c(0, 0))).unwrap();
+        assert_eq!(board.owner(c(0, 0)), Some(1));
+        assert_eq!(board.current_turn(), 0);
+        let board = board.advance(Move::Place(c(1, 0))).unwrap();
+        assert_eq!(board.current_turn(), 1);
+
+        let same_cells = Board::new((3, 3))
+            .with_cell(c(0, 0), token(1))
+            .with_cell(c(1, 0), token(0));
+        assert_eq!(
+            board.count_victory_points(),
+            same_cells.count_victory_points()
+        );
+    }
+
+    #[test]
+    fn games_without_swaps_fill_the_board() {
+        let mut start = Board::new((3, 3));
+        start.rules.allow_swaps = false;
+
+        for board in random_positions(20, &start) {
+            let empty = board.get_cells().filter(|(cell, _)| cell.is_none()).count();
+            if board.check_win_condition() == WinState::NotOver {
+                assert_eq!(board.get_legal_moves().len(), empty);
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut board = start;
+        let mut plies = 0;
+        while let Some(new_state) = board.apply_random_move(&mut rng) {
+            board = new_state;
+            plies += 1;
+        }
+        assert_eq!(plies, 9);
+        assert_ne!(board.check_win_condition(), WinState::NotOver);
+    }
+
+    #[test]
+    fn adjacent_placement() {
+        let board = Board::builder((3, 3))
+            .placement(PlacementRule::AdjacentToOwn)
+            .build()
+            .unwrap();
+        assert_eq!(board.get_legal_moves().len(), 9);
+
+        let board = board.with_cell(c(0, 0), token(0));
+        let mut placements: Vec<Move> = board
+            .get_legal_moves()
+            .into_iter()
+            .filter(|move_| matches!(move_, Move::Place(_)))
+            .collect();
+        placements.sort_by_key(|move_| match move_ {
+            Move::Place(coordinate) => (coordinate.y, coordinate.x),
+            Move::Swap(..) => unreachable!(),
+        });
+        assert_eq!(placements, [Move::Place(c(1, 0)), Move::Place(c(0, 1))]);
+    }
+
+    #[test]
+    fn locks_can_be_toggled() {
+        let mut board = Board::from_compact("3x1/t0/x..").unwrap();
+
+        board.set_locked(c(0, 0), true).unwrap();
+        assert!(board.is_locked(c(0, 0)));
+        board.set_locked(c(0, 0), false).unwrap();
+        assert!(!board.is_locked(c(0, 0)));
+
+        assert_eq!(
+            board.set_locked(c(1, 0), true),
+            Err(MoveError::Empty(c(1, 0)))
+        );
+        assert_eq!(
+            board.set_locked(c(3, 0), true),
+            Err(MoveError::NotPlayable(c(3, 0)))
+        );
+    }
+
+    #[test]
+    fn fill_ratio_of_empty_and_half_full_boards() {
+        assert_eq!(Board::new((3, 3)).total_cells(), 9);
+        assert_eq!(Board::new((3, 3)).fill_ratio(), 0.0);
+        assert_eq!(
+            Board::from_compact("2x2/t0/x./o.").unwrap().fill_ratio(),
+            0.5
+        );
+    }
+
+    #[test]
+    fn rows_and_columns_of_a_wide_board() {
+        let board = Board::from_compact("4x2/t0/x.../...o").unwrap();
+        assert_eq!(board.rows().len(), 2);
+        assert_eq!(board.columns().len(), 4);
+
+        let rows: Vec<Vec<Cell>> = board.rows().collect();
+        let columns: Vec<Vec<Cell>> = board.columns().collect();
+        assert!(rows.iter().all(|row| row.len() == 4));
+        assert!(columns.iter().all(|column| column.len() == 2));
+        assert_eq!(columns[0][0], rows[0][0]);
+        assert_eq!(columns[3][1], rows[1][3]);
+    }
+
+    #[test]
+    fn margin_sign_and_magnitude() {
+        let board = with_threshold("4x2/t0/xx../oooo", 0);
+
+        assert_eq!(board.count_victory_points(), [2, 4]);
+        assert_eq!(board.margin(), -2);
+        assert_eq!(board.margin_for(1), 2);
+        assert_eq!(Board::new((3, 3)).margin(), 0);
+
+        let three_players = Board::builder((4, 2))
+            .player_count(3)
+            .victory_threshold(0)
+            .build()
+            .unwrap()
+            .with_cell(c(0, 0), token(0))
+            .with_cell(c(1, 0), token(0))
+            .with_cell(c(0, 1), token(1))
+            .with_cell(c(3, 0), token(2))
+            .with_cell(c(3, 1), token(2));
+        assert_eq!(three_players.margin_for(1), -2);
+        assert_eq!(three_players.margin_for(2), 0);
+    }
+
+    #[test]
+    fn decisive_move_of_a_won_game() {
+        let mut game = Game::new(with_threshold("3x3/t0/.../.../...", 1));
+        let (mut solver, mut random) = (Solver::default(), RandomController::new(0));
+        game.play_match(&mut [&mut solver, &mut random], None);
+        assert_eq!(game.outcome().winner(), Some(0));
+
+        let (index, move_) = game.decisive_move().unwrap();
+        assert_eq!(game.moves()[index], move_);
+        assert_eq!(index, 0);
+
+        let mut drawn = Game::new(Board::new((2, 2)));
+        let (mut first, mut second) = (Solver::default(), Solver::default());
+        drawn.play_match(&mut [&mut first, &mut second], None);
+        assert_eq!(drawn.outcome().winner(), None);
+        assert_eq!(drawn.decisive_move(), None);
+    }
+
+    #[test]
+    fn locked_count_and_ratio() {
+        let board = Board::from_compact("3x1/t0/XXo").unwrap();
+
+        assert_eq!(board.locked_count(), 2);
+        assert!((board.locked_ratio() - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(Board::new((3, 3)).locked_ratio(), 0.0);
+    }
+
+    #[test]
+    fn snapshot_of_a_known_board() {
+        let board = with_threshold("3x2/t1/XX./o_#", 0);
+
+        assert_eq!(
+            board.snapshot(),
+            "size: 3x2\n\
+             turn: 1\n\
+             grid:\n\
+             XX.\n\
+             o_#\n\
+             points: 2 0\n\
+             locked: 0,0 1,0\n"
+        );
+    }
+
+    #[test]
+    fn shared_tt_keeps_deeper_entries() {
+        let entry = |depth, score| TranspositionEntry {
+            depth,
+            best_move: None,
+            score,
+            bound: Bound::Exact,
+        };
+        let tt = SharedTT::new();
+        assert!(tt.is_empty());
+
+        tt.store(1, entry(Some(4), 5));
+        tt.store(1, entry(Some(2), 7));
+        assert_eq!(tt.get(1).unwrap().score, 5);
+        tt.store(1, entry(None, 9));
+        assert_eq!(tt.get(1).unwrap().score, 9);
+        assert_eq!(tt.len(), 1);
+    }
+
+    #[test]
+    fn parallel_search_matches_sequential() {
+        for board in random_positions(10, &with_threshold("3x3/t0/.../.../...", 1)) {
+            let turn = board.current_turn();
+            let sequential = Solver::default().find_best_move_scored(&board, turn);
+            let parallel = Solver::default().find_best_move_parallel(&board, turn, 4);
+            assert_eq!(parallel, sequential, "{}", board.compact());
+        }
+
+        let board = Board::new((4, 4));
+        assert_eq!(
+            Solver::with_depth_limit(3).find_best_move_parallel(&board, 0, 3),
+            Solver::with_depth_limit(3).find_best_move_scored(&board, 0)
+        );
+    }
+
+    #[test]
+    fn custom_turn_schedule() {
+        let board = Board::builder((3, 3))
+            .turn_schedule(vec![0, 0, 1])
+            .build()
+            .unwrap();
+
+        let mut board = board;
+        let mut movers = Vec::new();
+        for x in 0..3 {
+            for y in 0..2 {
+                movers.push(board.current_turn());
+                board = board.advance(Move::Place(c(x, y))).unwrap();
+            }
+        }
+        assert_eq!(movers, [0, 0, 1, 0, 0, 1]);
+        assert_eq!(board.owner(c(0, 0)), Some(0));
+        assert_eq!(board.owner(c(0, 1)), Some(0));
+        assert_eq!(board.owner(c(1, 0)), Some(1));
+    }
+
+    #[test]
+    fn forks() {
+        let board = with_threshold("5x1/t0/.....", 0);
+        assert_eq!(
+            board.find_forks(0),
+            [
+                Move::Place(c(1, 0)),
+                Move::Place(c(2, 0)),
+                Move::Place(c(3, 0))
+            ]
+        );
+
+        assert!(with_threshold("3x1/t0/.o.", 0).find_forks(0).is_empty());
+    }
+
+    #[test]
+    fn komi_compensates_the_second_player() {
+        let mut board = with_threshold("3x2/t0/XXX/OO#", 0);
+        assert_eq!(board.count_victory_points(), [3, 2]);
+        assert_eq!(board.check_win_condition(), WinState::Winner(0));
+
+        board.rules.komi = 1;
+        assert_eq!(board.check_win_condition(), WinState::Draw);
+        board.rules.komi = 2;
+        assert_eq!(board.check_win_condition(), WinState::Winner(1));
+    }
+
+    #[test]
+    fn diff_reports_placed_and_locked_cells() {
+        let board = with_threshold("3x1/t0/x..", 0);
+        let new_state = board.advance(Move::Place(c(1, 0))).unwrap();
+
+        let locked = || {
+            Some(Token {
+                player: 0,
+                locked: true,
+            })
+        };
+        assert_eq!(
+            board.diff(&new_state),
+            [(c(0, 0), token(0), locked()), (c(1, 0), None, locked())]
+        );
+        assert!(board.diff(&board).is_empty());
+    }
+
+    #[test]
+    fn quicker_wins_are_preferred() {
+        // (0,0), (2,0) and (4,0) reach the target at once, (5,0) only later
+        let mut board = with_threshold("7x1/t0/.x.x..o", 0);
+        board.rules.target_points = Some(2);
+
+        let slower = board.advance(Move::Place(c(5, 0))).unwrap();
+        let (slower_score, _) = Solver::default().find_best_move_scored(&slower, 0).unwrap();
+        assert!(is_decisive(slower_score) && slower_score > 0);
+
+        let (score, moves) = Solver::default().best_moves(&board, 0).unwrap();
+        assert_eq!(score, WIN_SCORE - 1);
+        assert!(add_ply(slower_score) < score);
+        assert_eq!(
+            moves,
+            [
+                Move::Place(c(0, 0)),
+                Move::Place(c(2, 0)),
+                Move::Place(c(4, 0))
+            ]
+        );
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn feature_planes() {
+        let board = Board::from_compact("3x2/t1/xO_/.#.").unwrap();
+        let planes = board.to_feature_planes();
+
+        assert_eq!(planes.shape(), [4, 2, 3]);
+        assert_eq!(planes[[0, 0, 0]], 1.0);
+        assert_eq!(planes[[1, 0, 1]], 1.0);
+        assert_eq!(planes[[2, 0, 1]], 1.0);
+        assert_eq!(planes[[3, 1, 0]], 1.0);
+        assert_eq!(planes[[3, 0, 2]], 0.0);
+        assert_eq!(planes.sum(), 3.0 + 5.0);
+    }
+
+    #[test]
+    fn cancelled_searches_return_a_legal_move() {
+        let board = Board::new((5, 5));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            flag.store(true, Ordering::Relaxed);
+        });
+
+        let started = Instant::now();
+        let (_, best_move) = Solver::default().search_cancellable(&board, 0, cancel);
+        canceller.join().unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(board.is_legal(best_move.unwrap()));
+    }
+
+    #[test]
+    fn solved_searches_stop_without_cancelling() {
+        let board = Board::new((2, 2));
+        let never = Arc::new(AtomicBool::new(false));
+
+        let started = Instant::now();
+        let (score, best_move) = Solver::default().search_cancellable(&board, 0, never);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(score, 0);
+        assert!(board.is_legal(best_move.unwrap()));
+    }
+
+    #[test]
+    fn mirrors() {
+        for board in random_positions(10, &Board::new((4, 3))) {
+            assert!(board.mirror_horizontal().is_mirror_of(&board));
+        }
+
+        let board = Board::from_compact("3x1/t0/x..").unwrap();
+        assert!(!board.is_mirror_of(&board));
+        assert!(!board.is_mirror_of(&Board::new((1, 3))));
+    }
+
+    #[test]
+    fn manhattan_balls() {
+        let board = Board::new((5, 5));
+        let mut orthogonal = board.cells_neighbor_coordinates(c(2, 2));
+        orthogonal.sort_by_key(|coordinate| (coordinate.y, coordinate.x));
+        assert_eq!(board.cells_within_manhattan(c(2, 2), 1), orthogonal);
+
+        let ball = board.cells_within_manhattan(c(2, 2), 2);
+        assert_eq!(ball.len(), 12);
+        for coordinate in [
+            c(1, 1),
+            c(3, 1),
+            c(1, 3),
+            c(3, 3),
+            c(2, 0),
+            c(0, 2),
+            c(4, 2),
+            c(2, 4),
+        ] {
+            assert!(ball.contains(&coordinate));
+        }
+        assert_eq!(board.cells_within_manhattan(c(0, 0), 2).len(), 5);
+    }
+
+    #[test]
+    fn frames_step_through_the_game() {
+        let mut game = Game::new(Board::new((3, 3)));
+        let (mut first, mut second) = (RandomController::new(3), RandomController::new(4));
+        game.play_match(&mut [&mut first, &mut second], None);
+
+        let frames = game.frames();
+        assert_eq!(frames.len(), game.moves().len() + 1);
+        assert_eq!(frames[0], Board::new((3, 3)));
+        assert_eq!(frames.last(), Some(game.board()));
+    }
+}
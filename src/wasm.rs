@@ -0,0 +1,108 @@
+//! JavaScript facade for WebAssembly builds.
+//!
+//! Boards and moves cross the boundary as JSON strings: boards in their
+//! serde form, moves in the `Board::legal_moves_json` format. The plain
+//! functions do the work and can be used natively; the `#[wasm_bindgen]`
+//! wrappers only turn errors into JS exceptions. Incoming boards are checked
+//! with `Board::from_json`, so a malformed one throws instead of aborting.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Board, GameError, JsonMove, Solver};
+
+/// Empty board with the default ruleset
+pub fn new_board_json(width: usize, height: usize) -> Result<String, GameError> {
+    let board = Board::builder((width, height)).build()?;
+
+    Ok(serde_json::to_string(&board)?)
+}
+
+pub fn legal_moves_json(board: &str) -> Result<String, GameError> {
+    let board = Board::from_json(board)?;
+
+    Ok(board.legal_moves_json())
+}
+
+/// Plays `move_` on `board`, rejecting illegal moves
+pub fn advance_json(board: &str, move_: &str) -> Result<String, GameError> {
+    let board = Board::from_json(board)?;
+    let new_state = board.play_move_json(move_)?;
+
+    Ok(serde_json::to_string(&new_state)?)
+}
+
+/// The solver's move for the player to move, searched `depth` plies deep,
+/// or `null` if the game is over
+pub fn best_move_json(board: &str, depth: u32) -> Result<String, GameError> {
+    let board = Board::from_json(board)?;
+    let best_move = Solver::with_depth_limit(depth)
+        .find_best_move(&board, board.current_turn)
+        .expect("search is depth limited")
+        .map(JsonMove::from);
+
+    Ok(serde_json::to_string(&best_move)?)
+}
+
+fn to_js(error: GameError) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+#[wasm_bindgen]
+pub fn new_board(width: usize, height: usize) -> Result<String, JsValue> {
+    new_board_json(width, height).map_err(to_js)
+}
+
+#[wasm_bindgen]
+pub fn legal_moves(board: &str) -> Result<String, JsValue> {
+    legal_moves_json(board).map_err(to_js)
+}
+
+#[wasm_bindgen]
+pub fn advance(board: &str, move_: &str) -> Result<String, JsValue> {
+    advance_json(board, move_).map_err(to_js)
+}
+
+#[wasm_bindgen]
+pub fn best_move(board: &str, depth: u32) -> Result<String, JsValue> {
+    best_move_json(board, depth).map_err(to_js)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_boards_are_errors() {
+        let board = new_board_json(3, 3).unwrap();
+        let mut short: serde_json::Value = serde_json::from_str(&board).unwrap();
+        short["cells"] = serde_json::json!([null, null]);
+        let short = short.to_string();
+
+        assert!(legal_moves_json(&board).is_ok());
+        assert!(matches!(
+            legal_moves_json(&short),
+            Err(GameError::Corrupt(_))
+        ));
+        assert!(advance_json(&short, r#"{"type":"place","x":0,"y":0}"#).is_err());
+        assert!(best_move_json(&short, 1).is_err());
+    }
+
+    #[test]
+    fn facade_round_trip() {
+        let board = new_board_json(3, 3).unwrap();
+        let moves: Vec<serde_json::Value> =
+            serde_json::from_str(&legal_moves_json(&board).unwrap()).unwrap();
+        assert_eq!(moves.len(), 9);
+
+        let board = advance_json(&board, &moves[4].to_string()).unwrap();
+        let best_move = best_move_json(&board, 2).unwrap();
+        let board = advance_json(&board, &best_move).unwrap();
+        assert_eq!(Board::from_json(&board).unwrap().fill_ratio(), 2.0 / 9.0);
+
+        assert!(advance_json(&board, &moves[4].to_string()).is_err());
+
+        let settled = Board::from_compact("3x3/t0/XOX/OXO/XO.").unwrap();
+        let settled = serde_json::to_string(&settled).unwrap();
+        assert_eq!(best_move_json(&settled, 2).unwrap(), "null");
+    }
+}